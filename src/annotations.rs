@@ -0,0 +1,72 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+// Extra data attached to cells by (row, column), kept separate from the board so that the solver
+// never sees it
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Annotations<T> {
+    map: HashMap<(usize, usize), T>,
+}
+
+impl<T> Annotations<T> {
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, row_column: (usize, usize)) -> Option<&T> {
+        self.map.get(&row_column)
+    }
+
+    // Returns the previous annotation for the cell, if any
+    pub fn set(&mut self, row_column: (usize, usize), value: T) -> Option<T> {
+        self.map.insert(row_column, value)
+    }
+
+    pub fn remove(&mut self, row_column: (usize, usize)) -> Option<T> {
+        self.map.remove(&row_column)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.map
+            .iter()
+            .map(|(&row_column, value)| (row_column, value))
+    }
+}
+
+impl<T> Default for Annotations<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_annotations() {
+        let mut annotations = Annotations::new();
+        assert_eq!(annotations.get((1, 2)), None);
+
+        assert_eq!(annotations.set((1, 2), "red".to_string()), None);
+        assert_eq!(annotations.get((1, 2)), Some(&"red".to_string()));
+        assert_eq!(
+            annotations.set((1, 2), "blue".to_string()),
+            Some("red".to_string())
+        );
+        annotations.set((0, 0), "note".to_string());
+
+        let mut all = annotations.iter().collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(
+            all,
+            vec![((0, 0), &"note".to_string()), ((1, 2), &"blue".to_string())]
+        );
+
+        assert_eq!(annotations.remove((0, 0)), Some("note".to_string()));
+        assert_eq!(annotations.get((0, 0)), None);
+    }
+}
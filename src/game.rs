@@ -1,12 +1,16 @@
+use crate::parse;
+use crate::solver::{abs_difference, direction_between, step_from, Solver};
 use array2d::Array2D;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
+use std::fmt;
+use std::ops::RangeInclusive;
 
 pub type Board = Array2D<Cell>;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Game {
-    pub board: Board,
+    board: Board,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -19,6 +23,7 @@ pub struct Cell {
 pub enum Pointer {
     Go(Direction),
     Final,
+    Blocked,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -35,16 +40,96 @@ pub enum Direction {
 
 pub type Number = usize;
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ArrowStyle {
+    // The arrows used by `to_strings`
+    Unicode,
+    // The tokens used by the text format, e.g. `ne` and `*`
+    Token,
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum Error {
     EmptyBoard,
     MultipleOfNumber(Number),
     NumberTooHigh(Number),
     NoZeroAllowed,
-    WrongFinalNumber { actual: Number, expected: Number },
+    WrongFinalNumber {
+        actual: Number,
+        expected: Number,
+    },
     FinalNumberWithDirection(Number, Direction),
+    WrongNumberOfCells {
+        actual: usize,
+        expected: usize,
+    },
+    OutOfBounds(usize, usize),
+    ClueMismatch {
+        actual: Number,
+        expected: Number,
+    },
+    NumberOnBlockedCell(Number),
+    RaggedRows {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidEncoding,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum MergeConflict {
+    DifferentDimensions,
+    PointerMismatch(usize, usize),
+    NumberMismatch {
+        row: usize,
+        column: usize,
+        numbers: (Number, Number),
+    },
+    // Both boards placed the same number, but in different cells
+    DuplicateNumber(Number),
+}
+
+// Something `Game::new_lenient` fixed on its way to a valid game
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Warning {
+    // The number was out of range, so the cell was left blank instead
+    DroppedNumber {
+        number: Number,
+        row: usize,
+        column: usize,
+    },
+}
+
+// How hard a puzzle is to solve, from `Game::difficulty_band`. Separate from the generator's
+// `Difficulty`, which says how many clues to leave.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum DifficultyBand {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+// Why a board is not a solution to a game
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SolutionError {
+    WrongDimensions,
+    PointerMismatch(usize, usize),
+    ClueMismatch(usize, usize),
+    InvalidGame(Error),
+    // The cell numbered one less than the final cell doesn't point toward it
+    FinalNotReachedFromPredecessor { number: Number, at: (usize, usize) },
+    NotSolved,
 }
 
+const DIFFICULTY_PLACEMENT_LIMIT: usize = 100_000;
+
+// Upper bounds on `difficulty_estimate` for each band below `DifficultyBand::Expert`
+const EASY_BELOW: f64 = 0.3;
+const MEDIUM_BELOW: f64 = 0.6;
+const HARD_BELOW: f64 = 0.85;
+
 macro_rules! cell {
     ($direction:tt) => {
         Cell::new(dir!($direction), None).unwrap();
@@ -88,242 +173,2397 @@ macro_rules! dir {
     ("*") => {
         Pointer::Final;
     };
+    ("#") => {
+        Pointer::Blocked;
+    };
+}
+
+// Build a validated board from bare tokens, e.g. `try_board![[e 1, e, s], [se, w 5, *9]]`. Gives a
+// `Result<Board, Error>`; `board!` is the same but panics on an invalid board.
+#[macro_export]
+macro_rules! try_board {
+    ($([$($pointer:tt $($number:literal)?),* $(,)?]),* $(,)?) => {
+        $crate::game::board_from_tokens(vec![$(vec![$((
+            $crate::board_pointer!($pointer),
+            None$(.or(Some($number)))?,
+        )),*]),*])
+    };
+}
+
+#[macro_export]
+macro_rules! board {
+    ($($rows:tt)*) => {
+        match $crate::try_board!($($rows)*) {
+            Ok(board) => board,
+            Err(e) => panic!("Invalid board: {:?}", e),
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! board_pointer {
+    (n) => {
+        $crate::game::Pointer::Go($crate::game::Direction::North)
+    };
+    (ne) => {
+        $crate::game::Pointer::Go($crate::game::Direction::Northeast)
+    };
+    (e) => {
+        $crate::game::Pointer::Go($crate::game::Direction::East)
+    };
+    (se) => {
+        $crate::game::Pointer::Go($crate::game::Direction::Southeast)
+    };
+    (s) => {
+        $crate::game::Pointer::Go($crate::game::Direction::South)
+    };
+    (sw) => {
+        $crate::game::Pointer::Go($crate::game::Direction::Southwest)
+    };
+    (w) => {
+        $crate::game::Pointer::Go($crate::game::Direction::West)
+    };
+    (nw) => {
+        $crate::game::Pointer::Go($crate::game::Direction::Northwest)
+    };
+    (*) => {
+        $crate::game::Pointer::Final
+    };
+    (#) => {
+        $crate::game::Pointer::Blocked
+    };
 }
 
 impl Game {
     #[allow(dead_code)]
     pub fn new(board: Board) -> Result<Self, Error> {
-        if board.num_elements() == 0 {
-            return Err(Error::EmptyBoard);
-        }
-        let max_number = board.num_elements();
-        let mut seen = HashSet::new();
+        validate_number_domain(&board)?;
+        Ok(Self { board })
+    }
 
-        for (pointer, number) in board
-            .elements_row_major_iter()
-            .flat_map(|cell| cell.pointer_number())
-        {
-            match pointer {
-                Pointer::Go(direction) => {
-                    if number == max_number {
-                        return Err(Error::FinalNumberWithDirection(number, direction));
-                    }
-                }
-                Pointer::Final => {
-                    if number != max_number {
-                        return Err(Error::WrongFinalNumber {
-                            actual: number,
-                            expected: max_number,
+    // Like `new`, but numbers that are out of range are cleared with a warning instead of making the
+    // board invalid. Any other problem is still an error.
+    #[allow(dead_code)]
+    pub fn new_lenient(mut board: Board) -> (Result<Self, Error>, Vec<Warning>) {
+        let max_number = chain_length(&board);
+        let mut warnings = Vec::new();
+        for row in 0..board.num_rows() {
+            for column in 0..board.num_columns() {
+                let cell = &mut board[(row, column)];
+                match cell.number {
+                    Some(number) if !cell.is_blocked() && (number == 0 || number > max_number) => {
+                        cell.number = None;
+                        warnings.push(Warning::DroppedNumber {
+                            number,
+                            row,
+                            column,
                         });
                     }
+                    _ => (),
                 }
-            };
-
-            if seen.contains(&number) {
-                return Err(Error::MultipleOfNumber(number));
             }
-            if number > max_number {
-                return Err(Error::NumberTooHigh(number));
-            }
-
-            seen.insert(number);
         }
+        (Self::new(board), warnings)
+    }
 
-        Ok(Self { board })
+    // The number invariants that `new` checks, for a board that isn't a game yet
+    #[allow(dead_code)]
+    pub fn validate_number_domain(board: &Board) -> Result<(), Error> {
+        validate_number_domain(board)
     }
 
-    pub fn example() -> Self {
-        Self {
-            board: Array2D::from_rows(&vec![
-                vec![cell!("e", 1), cell!("e"), cell!("s"), cell!("w", 3)],
-                vec![cell!("s"), cell!("s", 12), cell!("w", 5), cell!("w")],
-                vec![cell!("se"), cell!("w"), cell!("e"), cell!("n")],
-                vec![cell!("e"), cell!("e"), cell!("n"), cell!("*", 16)],
-            ])
-            .unwrap(),
+    // Whether the board is structurally legal: the numbers are in range, unique, and only the final
+    // cell has the highest number. This says nothing about whether the puzzle has a solution, which
+    // only the solver can tell.
+    #[allow(dead_code)]
+    pub fn is_well_formed(board: &Board) -> bool {
+        validate_number_domain(board).is_ok()
+    }
+
+    #[allow(dead_code)]
+    pub fn check_shape(rows: &[Vec<Cell>]) -> Result<(), Error> {
+        let expected = match rows.first() {
+            Some(first) if !first.is_empty() => first.len(),
+            _ => return Err(Error::EmptyBoard),
+        };
+        match rows.iter().position(|row| row.len() != expected) {
+            Some(row) => Err(Error::RaggedRows {
+                row,
+                expected,
+                found: rows[row].len(),
+            }),
+            None => Ok(()),
         }
     }
 
-    pub fn to_strings(&self) -> Vec<String> {
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn into_board(self) -> Board {
         self.board
-            .rows_iter()
-            .map(|row| self.row_to_string(row))
-            .collect()
     }
 
-    fn row_to_string<'a, T>(&'a self, row_iter: T) -> String
-    where
-        T: Iterator<Item = &'a Cell>,
-    {
-        row_iter
-            .map(|cell| self.cell_to_string(cell))
-            .collect::<Vec<_>>()
-            .join("|")
-            .into()
+    // Compares dimensions and then each cell in row-major order, regardless of how `Array2D` stores
+    // them internally
+    #[allow(dead_code)]
+    pub fn cells_equal(&self, other: &Game) -> bool {
+        self.board.num_rows() == other.board.num_rows()
+            && self.board.num_columns() == other.board.num_columns()
+            && self
+                .board
+                .elements_row_major_iter()
+                .eq(other.board.elements_row_major_iter())
     }
 
-    fn cell_to_string(&self, cell: &Cell) -> String {
-        let pointer_string = match cell.pointer {
-            Pointer::Go(d) => d.to_unicode_arrow(),
-            Pointer::Final => "☆",
+    // Every cell whose arrow passes through the given cell, in row-major order
+    #[allow(dead_code)]
+    pub fn predecessors(&self, row: usize, column: usize) -> Vec<(usize, usize)> {
+        match self.board.get(row, column) {
+            Some(cell) if !cell.is_blocked() => (),
+            _ => return Vec::new(),
+        }
+        self.enumerate()
+            .filter(
+                |&(from, cell)| match direction_between(from, (row, column)) {
+                    Some(direction) => cell.pointer == Pointer::Go(direction),
+                    None => false,
+                },
+            )
+            .map(|(from, _)| from)
+            .collect()
+    }
+
+    // Empty cells that no arrow could lead into and that can't be the start, which make the board
+    // unsolvable
+    #[allow(dead_code)]
+    pub fn isolated_cells(&self) -> Vec<(usize, usize)> {
+        let numbers = self
+            .board
+            .elements_row_major_iter()
+            .filter_map(|cell| cell.number)
+            .collect::<HashSet<_>>();
+        let could_be_start = |cell: &Cell| {
+            !numbers.contains(&1)
+                && (cell.pointer != Pointer::Final || chain_length(&self.board) == 1)
         };
-        let max_num = self.board.num_elements();
-        let num_digits = log10(max_num);
-        let number_string = match cell.number {
-            Some(n) => n.to_string(),
-            None => "".to_string(),
+        // A numbered cell can only lead into an empty cell if the number after it isn't placed yet
+        let could_lead_in = |row_column: (usize, usize)| match self.board[row_column].number {
+            Some(number) => !numbers.contains(&(number + 1)),
+            None => true,
         };
-        format!(
-            "{: >width$} {}",
-            number_string,
-            pointer_string,
-            width = num_digits
-        )
+        self.enumerate()
+            .filter(|(_, cell)| cell.number.is_none() && !cell.is_blocked())
+            .filter(|(_, cell)| !could_be_start(cell))
+            .filter(|&((row, column), _)| {
+                !self
+                    .predecessors(row, column)
+                    .into_iter()
+                    .any(could_lead_in)
+            })
+            .map(|(row_column, _)| row_column)
+            .collect()
     }
-}
 
-impl Cell {
-    pub fn new(pointer: Pointer, number: Option<Number>) -> Result<Self, Error> {
-        if number == Some(0) {
-            return Err(Error::NoZeroAllowed);
+    // Groups of cells linked by a possible step from one to the next, ignoring which way the step
+    // goes. Each group is in row-major order, and the groups are ordered by their first cell. A
+    // solvable board has a single group.
+    #[allow(dead_code)]
+    pub fn components(&self) -> Vec<Vec<(usize, usize)>> {
+        let num_columns = self.board.num_columns();
+        let numbers = self
+            .board
+            .elements_row_major_iter()
+            .filter_map(|cell| cell.number)
+            .collect::<HashSet<_>>();
+        let could_follow = |from: &Cell, to: &Cell| match (from.number, to.number) {
+            (Some(from), Some(to)) => to == from + 1,
+            (Some(from), None) => !numbers.contains(&(from + 1)),
+            (None, Some(to)) => to != 1,
+            (None, None) => true,
+        };
+
+        let mut parents = (0..self.board.num_elements()).collect::<Vec<_>>();
+        fn find_root(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
         }
-        Ok(Self { pointer, number })
+        for (from, cell) in self.enumerate() {
+            let direction = match cell.pointer {
+                Pointer::Go(direction) => direction,
+                Pointer::Final | Pointer::Blocked => continue,
+            };
+            let mut to = from;
+            while let Some(next) = step_from(to, direction, 1) {
+                to = next;
+                let next_cell = match self.board.get(to.0, to.1) {
+                    Some(next_cell) => next_cell,
+                    None => break,
+                };
+                if !next_cell.is_blocked() && could_follow(cell, next_cell) {
+                    let a = find_root(&mut parents, from.0 * num_columns + from.1);
+                    let b = find_root(&mut parents, to.0 * num_columns + to.1);
+                    parents[a] = b;
+                }
+            }
+        }
+
+        let mut components = Vec::<Vec<(usize, usize)>>::new();
+        let mut component_of_root = HashMap::new();
+        for ((row, column), cell) in self.enumerate() {
+            if cell.is_blocked() {
+                continue;
+            }
+            let root = find_root(&mut parents, row * num_columns + column);
+            let i = *component_of_root.entry(root).or_insert_with(|| {
+                components.push(Vec::new());
+                components.len() - 1
+            });
+            components[i].push((row, column));
+        }
+        components
     }
 
-    pub fn pointer_number(self) -> Option<(Pointer, Number)> {
-        self.number.map(|n| (self.pointer, n))
+    // The numbers an empty cell could hold considering only the clues: some cell that could hold the
+    // number before must point at it, and some cell that could hold the number after must be along
+    // its arrow
+    #[allow(dead_code)]
+    pub fn possible_values_for_cell(&self, row: usize, column: usize) -> Vec<Number> {
+        let cell = match self.board.get(row, column) {
+            Some(cell) if !cell.is_blocked() => *cell,
+            _ => return Vec::new(),
+        };
+        if let Some(number) = cell.number {
+            return vec![number];
+        }
+        let max_number = chain_length(&self.board);
+        let clues = self
+            .enumerate()
+            .filter_map(|(row_column, cell)| Some((cell.number?, row_column)))
+            .collect::<HashMap<_, _>>();
+        let predecessors = self.predecessors(row, column);
+        let successors = match cell.pointer {
+            Pointer::Go(direction) => self
+                .enumerate()
+                .filter(|&(to, to_cell)| {
+                    !to_cell.is_blocked() && direction_between((row, column), to) == Some(direction)
+                })
+                .map(|(to, _)| to)
+                .collect(),
+            Pointer::Final | Pointer::Blocked => Vec::new(),
+        };
+        // A neighbor with no clue could hold any number
+        let fits = |neighbors: &[(usize, usize)], number| match clues.get(&number) {
+            Some(clue) => neighbors.contains(clue),
+            None => neighbors
+                .iter()
+                .any(|&neighbor| self.board[neighbor].number.is_none()),
+        };
+        self.numbers()
+            .filter(|number| !clues.contains_key(number))
+            .filter(|&number| (number == max_number) == (cell.pointer == Pointer::Final))
+            .filter(|&number| number == 1 || fits(&predecessors, number - 1))
+            .filter(|&number| number == max_number || fits(&successors, number + 1))
+            .collect()
     }
-}
 
-impl<'a> TryFrom<&'a str> for Direction {
-    type Error = &'a str;
+    #[allow(dead_code)]
+    pub fn number_grid(&self) -> Array2D<Option<Number>> {
+        number_grid(&self.board)
+    }
 
-    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        Ok(match value {
-            "n" => Self::North,
-            "ne" => Self::Northeast,
-            "e" => Self::East,
-            "se" => Self::Southeast,
-            "s" => Self::South,
-            "sw" => Self::Southwest,
-            "w" => Self::West,
-            "nw" => Self::Northwest,
-            _ => return Err(value),
-        })
+    // Which cells have a number filled in
+    #[allow(dead_code)]
+    pub fn clue_mask(&self) -> Array2D<bool> {
+        let mask = self
+            .board
+            .elements_row_major_iter()
+            .map(|cell| cell.number.is_some())
+            .collect::<Vec<_>>();
+        Array2D::from_row_major(&mask, self.board.num_rows(), self.board.num_columns())
+            .expect("Same dimensions as board")
     }
-}
 
-impl Direction {
-    fn to_unicode_arrow(self) -> &'static str {
-        match self {
-            Self::North => "⇑",
-            Self::Northeast => "⇗",
-            Self::East => "⇒",
-            Self::Southeast => "⇘",
-            Self::South => "⇓",
-            Self::Southwest => "⇙",
-            Self::West => "⇐",
-            Self::Northwest => "⇖",
-        }
+    #[allow(dead_code)]
+    pub fn with_cell(&self, row: usize, column: usize, cell: Cell) -> Result<Game, Error> {
+        let mut board = self.board.clone();
+        *board
+            .get_mut(row, column)
+            .ok_or(Error::OutOfBounds(row, column))? = cell;
+        Game::new(board)
     }
-}
 
-fn log10(num: usize) -> usize {
-    let num = num as f64;
-    let exponent = num.log10();
-    let exponent_ceil = exponent.ceil();
-    exponent_ceil as usize
-}
+    #[allow(dead_code)]
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.board.num_rows(), self.board.num_columns())
+    }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    // Every number the solution uses. Blocked cells are left out of the count.
+    #[allow(dead_code)]
+    pub fn numbers(&self) -> RangeInclusive<Number> {
+        1..=chain_length(&self.board)
+    }
 
-    #[test]
-    fn valid_board() {
-        let board = Array2D::from_rows(&vec![
-            vec![cell!("e", 1), cell!("e"), cell!("s")],
-            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
-            vec![cell!("e"), cell!("w"), cell!("*", 9)],
-        ])
-        .unwrap();
-        let result = Game::new(board);
-        println!("{:?}", result);
-        assert!(result.is_ok());
+    #[allow(dead_code)]
+    pub fn num_clues(&self) -> usize {
+        self.board
+            .elements_row_major_iter()
+            .filter(|cell| cell.number.is_some())
+            .count()
     }
 
-    #[test]
-    fn empty_board() {
-        let board = Array2D::from_rows(&vec![]).unwrap();
-        let result = Game::new(board);
-        println!("{:?}", result);
-        assert_eq!(result, Err(Error::EmptyBoard));
+    // `None` off the board, where indexing the board directly would panic
+    #[allow(dead_code)]
+    pub fn cell_at(&self, row: usize, column: usize) -> Option<&Cell> {
+        self.board.get(row, column)
     }
 
-    #[test]
-    fn multiple_numbers() {
-        let board = Array2D::from_rows(&vec![
-            vec![cell!("e", 1), cell!("e"), cell!("s")],
-            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
-            vec![cell!("e", 5), cell!("w"), cell!("*", 9)],
-        ])
-        .unwrap();
-        let result = Game::new(board);
-        println!("{:?}", result);
-        assert_eq!(result, Err(Error::MultipleOfNumber(5)));
+    #[allow(dead_code)]
+    pub fn index_of(&self, number: Number) -> Option<(usize, usize)> {
+        self.enumerate()
+            .find(|(_, cell)| cell.number == Some(number))
+            .map(|(row_column, _)| row_column)
     }
 
-    #[test]
-    fn number_too_high() {
-        let board = Array2D::from_rows(&vec![
-            vec![cell!("e", 1), cell!("e"), cell!("s")],
-            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
-            vec![cell!("e", 10), cell!("w"), cell!("*", 9)],
-        ])
-        .unwrap();
-        let result = Game::new(board);
-        println!("{:?}", result);
-        assert_eq!(result, Err(Error::NumberTooHigh(10)));
+    #[allow(dead_code)]
+    pub fn final_index(&self) -> Option<(usize, usize)> {
+        self.enumerate()
+            .find(|(_, cell)| cell.pointer == Pointer::Final)
+            .map(|(row_column, _)| row_column)
     }
 
-    #[test]
-    fn number_zero() {
-        assert_eq!(
-            Cell::new(Pointer::Go(Direction::East), Some(0)),
-            Err(Error::NoZeroAllowed)
+    // A one-line description such as `4x4, 5 clues, start (0,0), final (3,3)`
+    #[allow(dead_code)]
+    pub fn summary(&self) -> String {
+        let position = |row_column: Option<(usize, usize)>| match row_column {
+            Some((row, column)) => format!("({},{})", row, column),
+            None => "unknown".to_string(),
+        };
+        let (num_rows, num_columns) = self.dimensions();
+        format!(
+            "{}x{}, {} clues, start {}, final {}",
+            num_rows,
+            num_columns,
+            self.num_clues(),
+            position(self.index_of(1)),
+            position(self.final_index()),
         )
     }
 
-    #[test]
-    fn wrong_final_number() {
-        let board = Array2D::from_rows(&vec![
-            vec![cell!("e", 1), cell!("e"), cell!("s")],
-            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
-            vec![cell!("e", 8), cell!("w"), cell!("*", 8)],
-        ])
-        .unwrap();
-        let result = Game::new(board);
-        assert_eq!(
-            result,
-            Err(Error::WrongFinalNumber {
-                actual: 8,
-                expected: 9,
-            })
-        );
+    pub fn example() -> Self {
+        Self {
+            board: Array2D::from_rows(&vec![
+                vec![cell!("e", 1), cell!("e"), cell!("s"), cell!("w", 3)],
+                vec![cell!("s"), cell!("s", 12), cell!("w", 5), cell!("w")],
+                vec![cell!("se"), cell!("w"), cell!("e"), cell!("n")],
+                vec![cell!("e"), cell!("e"), cell!("n"), cell!("*", 16)],
+            ])
+            .unwrap(),
+        }
     }
 
-    #[test]
-    fn final_number_with_direction() {
-        let board = Array2D::from_rows(&vec![
-            vec![cell!("e", 1), cell!("e"), cell!("s")],
-            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
-            vec![cell!("e", 8), cell!("w"), cell!("e", 9)],
+    #[allow(dead_code)]
+    pub fn to_flat(&self) -> (Vec<Cell>, usize, usize) {
+        (
+            self.board.as_row_major(),
+            self.board.num_rows(),
+            self.board.num_columns(),
+        )
+    }
+
+    #[allow(dead_code)]
+    pub fn from_flat(cells: Vec<Cell>, num_rows: usize, num_columns: usize) -> Result<Self, Error> {
+        let expected = num_rows * num_columns;
+        if cells.len() != expected {
+            return Err(Error::WrongNumberOfCells {
+                actual: cells.len(),
+                expected,
+            });
+        }
+        let board = Array2D::from_row_major(&cells, num_rows, num_columns)
+            .expect("Number of cells already checked");
+        Self::new(board)
+    }
+
+    #[allow(dead_code)]
+    pub fn enumerate(&self) -> impl Iterator<Item = ((usize, usize), &Cell)> {
+        self.board.enumerate_row_major()
+    }
+
+    // Each row's cells along with their column
+    #[allow(dead_code)]
+    pub fn iter_rows(&self) -> impl Iterator<Item = impl Iterator<Item = (usize, &Cell)>> {
+        let board = &self.board;
+        (0..board.num_rows()).map(move |row| {
+            (0..board.num_columns()).map(move |column| (column, &board[(row, column)]))
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn solution_path(&self) -> Option<Vec<(usize, usize)>> {
+        let mut path = vec![None; chain_length(&self.board)];
+        for (row_column, cell) in self.enumerate() {
+            if cell.is_blocked() {
+                continue;
+            }
+            let number = cell.number?;
+            *path.get_mut(number - 1)? = Some(row_column);
+        }
+        let path = path.into_iter().collect::<Option<Vec<_>>>()?;
+        for window in path.windows(2) {
+            let pointer = Pointer::Go(direction_between(window[0], window[1])?);
+            if self.board[window[0]].pointer != pointer {
+                return None;
+            }
+        }
+        Some(path)
+    }
+
+    // The cells numbered 1 through `k` on a solved board
+    #[allow(dead_code)]
+    pub fn prefix_path(&self, k: Number) -> Option<Vec<(usize, usize)>> {
+        let mut path = self.solution_path()?;
+        if k > path.len() {
+            return None;
+        }
+        path.truncate(k);
+        Some(path)
+    }
+
+    #[allow(dead_code)]
+    pub fn is_solved(&self) -> bool {
+        self.solution_path().is_some()
+    }
+
+    // Check that `solution` is a solved version of this puzzle: the same arrows, with every clue kept
+    #[allow(dead_code)]
+    pub fn verify_solution(&self, solution: &Board) -> Result<(), SolutionError> {
+        if solution.num_rows() != self.board.num_rows()
+            || solution.num_columns() != self.board.num_columns()
+        {
+            return Err(SolutionError::WrongDimensions);
+        }
+        for ((row_column, clue), cell) in self.enumerate().zip(solution.elements_row_major_iter()) {
+            if clue.pointer != cell.pointer {
+                return Err(SolutionError::PointerMismatch(row_column.0, row_column.1));
+            }
+            if clue.number.is_some() && clue.number != cell.number {
+                return Err(SolutionError::ClueMismatch(row_column.0, row_column.1));
+            }
+        }
+        let game = Game::new(solution.clone()).map_err(SolutionError::InvalidGame)?;
+        // Checked on its own since a last hop that misses is easy to overlook
+        let number = chain_length(&game.board) - 1;
+        if let (Some(at), Some(final_index)) = (game.index_of(number), game.final_index()) {
+            if direction_between(at, final_index).map(Pointer::Go) != Some(game.board[at].pointer) {
+                return Err(SolutionError::FinalNotReachedFromPredecessor { number, at });
+            }
+        }
+        if game.is_solved() {
+            Ok(())
+        } else {
+            Err(SolutionError::NotSolved)
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn solution_directions(&self) -> Option<Vec<Direction>> {
+        let path = self.solution_path()?;
+        path[..path.len() - 1]
+            .iter()
+            .map(|&row_column| match self.board[row_column].pointer {
+                Pointer::Go(direction) => Some(direction),
+                Pointer::Final | Pointer::Blocked => None,
+            })
+            .collect()
+    }
+
+    #[allow(dead_code)]
+    pub fn apply_path(board: &Board, path: &[(usize, usize)]) -> Result<Board, Error> {
+        let expected = chain_length(board);
+        if path.len() != expected {
+            return Err(Error::WrongNumberOfCells {
+                actual: path.len(),
+                expected,
+            });
+        }
+        let mut board = board.clone();
+        for (i, &(row, column)) in path.iter().enumerate() {
+            let number = i + 1;
+            let cell = board
+                .get_mut(row, column)
+                .ok_or(Error::OutOfBounds(row, column))?;
+            if let Some(actual) = cell.number {
+                if actual != number {
+                    return Err(Error::ClueMismatch {
+                        actual,
+                        expected: number,
+                    });
+                }
+            }
+            *cell = cell.with_number(Some(number))?;
+        }
+        Ok(Self::new(board)?.board)
+    }
+
+    // A compact form of the solution: the starting cell, then a direction and distance for each step,
+    // e.g. `0,0:e1,s2,se1`. Directions alone aren't enough, since an arrow can lead to any cell along
+    // it, and without the start the first step has nowhere to begin.
+    #[allow(dead_code)]
+    pub fn encode_solution(&self) -> Option<String> {
+        let path = self.solution_path()?;
+        let steps = path
+            .windows(2)
+            .map(|window| {
+                let (from, to) = (window[0], window[1]);
+                let direction = direction_between(from, to)?;
+                let distance = abs_difference(from.0, to.0).max(abs_difference(from.1, to.1));
+                Some(format!(
+                    "{}{}",
+                    parse::direction_to_token(direction),
+                    distance
+                ))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let (row, column) = path[0];
+        Some(format!("{},{}:{}", row, column, steps.join(",")))
+    }
+
+    #[allow(dead_code)]
+    pub fn decode_solution(board: &Board, encoded: &str) -> Result<Board, Error> {
+        let parse_number = |text: &str| text.parse::<usize>().map_err(|_| Error::InvalidEncoding);
+        let mut parts = encoded.splitn(2, ':');
+        let start = parts.next().ok_or(Error::InvalidEncoding)?;
+        let steps = parts.next().ok_or(Error::InvalidEncoding)?;
+        let mut start = start.splitn(2, ',');
+        let row = parse_number(start.next().ok_or(Error::InvalidEncoding)?)?;
+        let column = parse_number(start.next().ok_or(Error::InvalidEncoding)?)?;
+
+        let mut path = vec![(row, column)];
+        for step in steps.split(',').filter(|step| !step.is_empty()) {
+            let split = step
+                .find(|c: char| c.is_ascii_digit())
+                .ok_or(Error::InvalidEncoding)?;
+            let direction =
+                parse::direction_from_token(&step[..split]).ok_or(Error::InvalidEncoding)?;
+            let distance = parse_number(&step[split..])?;
+            let current = *path.last().expect("Path starts with one cell");
+            if board.get(current.0, current.1).map(|cell| cell.pointer)
+                != Some(Pointer::Go(direction))
+            {
+                return Err(Error::InvalidEncoding);
+            }
+            path.push(step_from(current, direction, distance).ok_or(Error::InvalidEncoding)?);
+        }
+
+        let solved = Self::apply_path(board, &path)?;
+        if Self::new(solved.clone())?.is_solved() {
+            Ok(solved)
+        } else {
+            Err(Error::InvalidEncoding)
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn checkpoints(&self) -> Vec<((usize, usize), Number)> {
+        let mut checkpoints = self
+            .enumerate()
+            .filter(|(_, cell)| cell.is_checkpoint())
+            .filter_map(|(row_column, cell)| Some((row_column, cell.number?)))
+            .collect::<Vec<_>>();
+        checkpoints.sort_by_key(|&(_, number)| number);
+        checkpoints
+    }
+
+    // A rough difficulty score between 0.0 (every number is forced) and 1.0 (lots of guessing).
+    // It averages two parts:
+    // - How much choice each missing number has given only the clues. A number with `n` candidate
+    //   cells contributes `1 - 1/n`, so forced numbers contribute nothing.
+    // - How much backtracking a bounded search needs, as `backtracks / (backtracks + cells)`. A
+    //   search that hits the placement limit counts as maximally hard.
+    #[allow(dead_code)]
+    pub fn difficulty_estimate(&self) -> f64 {
+        let num_elements = chain_length(&self.board);
+
+        let profile = Solver::new(self.board.clone()).branching_profile();
+        let branching = profile
+            .iter()
+            .map(|&candidates| match candidates {
+                0 => 0.0,
+                n => 1.0 - 1.0 / n as f64,
+            })
+            .sum::<f64>()
+            / num_elements as f64;
+
+        let stats = Solver::search_stats(self.board.clone(), DIFFICULTY_PLACEMENT_LIMIT);
+        let search = if stats.placements >= DIFFICULTY_PLACEMENT_LIMIT {
+            1.0
+        } else {
+            stats.backtracks as f64 / (stats.backtracks + num_elements) as f64
+        };
+
+        (branching + search) / 2.0
+    }
+
+    // `difficulty_estimate` as a named band: below 0.3 is easy, below 0.6 medium, below 0.85 hard,
+    // and anything higher expert
+    #[allow(dead_code)]
+    pub fn difficulty_band(&self) -> DifficultyBand {
+        let estimate = self.difficulty_estimate();
+        if estimate < EASY_BELOW {
+            DifficultyBand::Easy
+        } else if estimate < MEDIUM_BELOW {
+            DifficultyBand::Medium
+        } else if estimate < HARD_BELOW {
+            DifficultyBand::Hard
+        } else {
+            DifficultyBand::Expert
+        }
+    }
+
+    // Lower means fewer choices for where each arrow leads, which generally makes a puzzle easier
+    #[allow(dead_code)]
+    pub fn mean_candidates(&self) -> f64 {
+        let counts = Solver::new(self.board.clone()).candidate_counts();
+        counts.iter().sum::<usize>() as f64 / counts.len() as f64
+    }
+
+    // A key to the arrow glyphs in the given style, e.g. `⇑ = N  ⇗ = NE  ...  ☆ = final`
+    #[allow(dead_code)]
+    pub fn legend(style: ArrowStyle) -> String {
+        let directions = [
+            (Direction::North, "N"),
+            (Direction::Northeast, "NE"),
+            (Direction::East, "E"),
+            (Direction::Southeast, "SE"),
+            (Direction::South, "S"),
+            (Direction::Southwest, "SW"),
+            (Direction::West, "W"),
+            (Direction::Northwest, "NW"),
+        ];
+        let glyph = |pointer| match style {
+            ArrowStyle::Unicode => match pointer {
+                Pointer::Go(direction) => direction.to_unicode_arrow().to_string(),
+                Pointer::Final => "☆".to_string(),
+                Pointer::Blocked => "■".to_string(),
+            },
+            ArrowStyle::Token => parse::cell_to_token(&Cell {
+                pointer,
+                number: None,
+            }),
+        };
+        directions
+            .iter()
+            .map(|&(direction, name)| (glyph(Pointer::Go(direction)), name))
+            .chain(std::iter::once((glyph(Pointer::Final), "final")))
+            .map(|(glyph, name)| format!("{} = {}", glyph, name))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
+    // The board as aligned parser tokens, which is much easier to read than the derived Debug output
+    // and can be parsed back in
+    #[allow(dead_code)]
+    pub fn debug_grid(&self) -> String {
+        let tokens = self
+            .board
+            .rows_iter()
+            .map(|row| row.map(parse::cell_to_token).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let width = tokens.iter().flatten().map(String::len).max().unwrap_or(0);
+        tokens
+            .iter()
+            .map(|row| {
+                let (last, rest) = row.split_last().expect("Rows are never empty");
+                let mut line = rest
+                    .iter()
+                    .map(|token| format!("{:<width$}", format!("{},", token), width = width + 2))
+                    .collect::<String>();
+                line.push_str(last);
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[allow(dead_code)]
+    pub fn direction_histogram(&self) -> HashMap<Direction, usize> {
+        let mut histogram = HashMap::new();
+        for cell in self.board.elements_row_major_iter() {
+            if let Pointer::Go(direction) = cell.pointer {
+                *histogram.entry(direction).or_insert(0) += 1;
+            }
+        }
+        histogram
+    }
+
+    #[allow(dead_code)]
+    pub fn sort_key(&self) -> Vec<(Pointer, Option<Number>)> {
+        board_sort_key(&self.board)
+    }
+
+    pub fn to_strings(&self) -> Vec<String> {
+        let max_num = self.board.num_elements();
+        self.to_strings_with_width(log10(max_num))
+    }
+
+    pub fn to_strings_with_width(&self, number_width: usize) -> Vec<String> {
+        self.board
+            .rows_iter()
+            .map(|row| self.row_to_string(row, number_width))
+            .collect()
+    }
+
+    // Like `to_strings_with_width`, but widen the number column if any number on the board needs it
+    // so that every arrow lines up
+    #[allow(dead_code)]
+    pub fn to_strings_aligned(&self, number_width: usize) -> Vec<String> {
+        let widest = self
+            .board
+            .elements_row_major_iter()
+            .filter_map(|cell| cell.number)
+            .map(|number| number.to_string().len())
+            .max()
+            .unwrap_or(0);
+        self.to_strings_with_width(number_width.max(widest))
+    }
+
+    // The numbers laid out as a grid, with lines drawn between each number and the next one through
+    // the gaps between cells: `-` and `|` along rows and columns, `\` and `/` along diagonals, and
+    // `X` where two diagonals cross. Empty cells are `.` and blocked cells are `#`.
+    #[allow(dead_code)]
+    pub fn to_connected_ascii(&self) -> String {
+        // Wide enough for the largest number
+        let width = self.board.num_elements().to_string().len();
+        let num_rows = self.board.num_rows() * 2 - 1;
+        let num_columns = self.board.num_columns() * (width + 1) - 1;
+        let mut canvas = vec![vec![' '; num_columns]; num_rows];
+        for ((row, column), cell) in self.enumerate() {
+            let text = match (cell.number, cell.pointer) {
+                (_, Pointer::Blocked) => "#".to_string(),
+                (Some(number), _) => number.to_string(),
+                (None, _) => ".".to_string(),
+            };
+            let end = column * (width + 1) + width;
+            for (i, c) in text.chars().enumerate() {
+                canvas[row * 2][end - text.len() + i] = c;
+            }
+        }
+
+        let mut positions = self
+            .enumerate()
+            .filter_map(|(row_column, cell)| cell.number.map(|number| (number, row_column)))
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+        for pair in positions.windows(2) {
+            let ((number, mut from), (next_number, to)) = (pair[0], pair[1]);
+            let direction = match direction_between(from, to) {
+                Some(direction) if next_number == number + 1 => direction,
+                _ => continue,
+            };
+            while from != to {
+                let next = step_from(from, direction, 1).expect("Steps toward a cell on the board");
+                let (row, column) = (from.0.min(next.0), from.1.min(next.1));
+                let (canvas_row, canvas_column, glyph) = match direction {
+                    Direction::East | Direction::West => {
+                        (row * 2, column * (width + 1) + width, '-')
+                    }
+                    Direction::North | Direction::South => {
+                        (row * 2 + 1, column * (width + 1) + width - 1, '|')
+                    }
+                    Direction::Southeast | Direction::Northwest => {
+                        (row * 2 + 1, column * (width + 1) + width, '\\')
+                    }
+                    Direction::Southwest | Direction::Northeast => {
+                        (row * 2 + 1, column * (width + 1) + width, '/')
+                    }
+                };
+                let gap = &mut canvas[canvas_row][canvas_column];
+                *gap = match (*gap, glyph) {
+                    ('\\', '/') | ('/', '\\') => 'X',
+                    _ => glyph,
+                };
+                from = next;
+            }
+        }
+
+        canvas
+            .into_iter()
+            .map(|line| line.into_iter().collect::<String>().trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Like `to_strings`, but with `marker` on both sides of each highlighted cell. Other cells get
+    // spaces instead so that the columns still line up.
+    #[allow(dead_code)]
+    pub fn to_strings_highlight(
+        &self,
+        highlight: &HashSet<(usize, usize)>,
+        marker: char,
+    ) -> Vec<String> {
+        let number_width = log10(self.board.num_elements());
+        (0..self.board.num_rows())
+            .map(|row| {
+                (0..self.board.num_columns())
+                    .map(|column| {
+                        let cell = self.cell_to_string(&self.board[(row, column)], number_width);
+                        let marker = if highlight.contains(&(row, column)) {
+                            marker
+                        } else {
+                            ' '
+                        };
+                        format!("{}{}{}", marker, cell, marker)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("|")
+            })
+            .collect()
+    }
+
+    fn row_to_string<'a, T>(&'a self, row_iter: T, number_width: usize) -> String
+    where
+        T: Iterator<Item = &'a Cell>,
+    {
+        row_iter
+            .map(|cell| self.cell_to_string(cell, number_width))
+            .collect::<Vec<_>>()
+            .join("|")
+            .into()
+    }
+
+    fn cell_to_string(&self, cell: &Cell, number_width: usize) -> String {
+        let pointer_string = match cell.pointer {
+            Pointer::Go(d) => d.to_unicode_arrow(),
+            Pointer::Final => "☆",
+            Pointer::Blocked => "■",
+        };
+        let number_string = match cell.number {
+            Some(n) => n.to_string(),
+            None => "".to_string(),
+        };
+        format!(
+            "{: >width$} {}",
+            number_string,
+            pointer_string,
+            width = number_width
+        )
+    }
+}
+
+// Operations on raw boards that don't need to be valid games
+#[allow(dead_code)]
+pub trait BoardExt: Sized {
+    fn crop(
+        &self,
+        top: usize,
+        left: usize,
+        num_rows: usize,
+        num_columns: usize,
+    ) -> Result<Self, Error>;
+
+    fn pad_to_square(&self, fill: Cell) -> Self;
+
+    fn merge(a: &Self, b: &Self) -> Result<Self, MergeConflict>;
+}
+
+impl BoardExt for Board {
+    fn crop(
+        &self,
+        top: usize,
+        left: usize,
+        num_rows: usize,
+        num_columns: usize,
+    ) -> Result<Self, Error> {
+        if num_rows == 0 || num_columns == 0 {
+            return Err(Error::EmptyBoard);
+        }
+        let bottom = top + num_rows - 1;
+        let right = left + num_columns - 1;
+        if self.get(bottom, right).is_none() {
+            return Err(Error::OutOfBounds(bottom, right));
+        }
+        let rows = (top..=bottom)
+            .map(|row| (left..=right).map(|column| self[(row, column)]).collect())
+            .collect::<Vec<_>>();
+        Ok(Array2D::from_rows(&rows).expect("Rows all have the same length"))
+    }
+
+    // Add rows to the bottom or columns to the right, meant to be used with a blocked `fill`
+    fn pad_to_square(&self, fill: Cell) -> Self {
+        let size = self.num_rows().max(self.num_columns());
+        let mut board = Array2D::filled_with(fill, size, size);
+        for (row_column, &cell) in self.enumerate_row_major() {
+            board[row_column] = cell;
+        }
+        board
+    }
+
+    // Combine the numbers placed on two copies of the same layout
+    fn merge(a: &Self, b: &Self) -> Result<Self, MergeConflict> {
+        if a.num_rows() != b.num_rows() || a.num_columns() != b.num_columns() {
+            return Err(MergeConflict::DifferentDimensions);
+        }
+        let mut merged = a.clone();
+        let mut seen = HashSet::new();
+        for ((row, column), &cell) in b.enumerate_row_major() {
+            let merged_cell = &mut merged[(row, column)];
+            if merged_cell.pointer != cell.pointer {
+                return Err(MergeConflict::PointerMismatch(row, column));
+            }
+            match (merged_cell.number, cell.number) {
+                (Some(a), Some(b)) if a != b => {
+                    return Err(MergeConflict::NumberMismatch {
+                        row,
+                        column,
+                        numbers: (a, b),
+                    })
+                }
+                (None, number) => merged_cell.number = number,
+                _ => (),
+            }
+            if let Some(number) = merged_cell.number {
+                if !seen.insert(number) {
+                    return Err(MergeConflict::DuplicateNumber(number));
+                }
+            }
+        }
+        Ok(merged)
+    }
+}
+
+impl<'a> IntoIterator for &'a Game {
+    type Item = &'a Cell;
+    type IntoIter = Box<dyn Iterator<Item = &'a Cell> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.board.elements_row_major_iter())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::EmptyBoard => write!(f, "the board has no cells on the chain"),
+            Error::MultipleOfNumber(number) => write!(f, "{} is on more than one cell", number),
+            Error::NumberTooHigh(number) => {
+                write!(f, "{} is higher than the number of cells", number)
+            }
+            Error::NoZeroAllowed => write!(f, "cells can't be numbered 0"),
+            Error::WrongFinalNumber { actual, expected } => write!(
+                f,
+                "the final cell is numbered {} but should be {}",
+                actual, expected
+            ),
+            Error::FinalNumberWithDirection(number, direction) => write!(
+                f,
+                "{} must be on the final cell, not a cell pointing {:?}",
+                number, direction
+            ),
+            Error::WrongNumberOfCells { actual, expected } => {
+                write!(f, "expected {} cells but found {}", expected, actual)
+            }
+            Error::OutOfBounds(row, column) => {
+                write!(f, "row {}, column {} is off the board", row + 1, column + 1)
+            }
+            Error::ClueMismatch { actual, expected } => write!(
+                f,
+                "the path puts {} on a cell numbered {}",
+                expected, actual
+            ),
+            Error::NumberOnBlockedCell(number) => write!(f, "{} is on a blocked cell", number),
+            Error::RaggedRows {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "row {} has {} cells but the first row has {}",
+                row + 1,
+                found,
+                expected
+            ),
+            Error::InvalidEncoding => write!(f, "the encoded solution is invalid"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Cell {
+    pub fn new(pointer: Pointer, number: Option<Number>) -> Result<Self, Error> {
+        if number == Some(0) {
+            return Err(Error::NoZeroAllowed);
+        }
+        if let (Pointer::Blocked, Some(number)) = (pointer, number) {
+            return Err(Error::NumberOnBlockedCell(number));
+        }
+        Ok(Self { pointer, number })
+    }
+
+    // The final cell may be left blank, but if it has a number it must be the length of the chain,
+    // which `Game::new` checks
+    pub fn final_cell(number: Number) -> Result<Self, Error> {
+        Self::new(Pointer::Final, Some(number))
+    }
+
+    pub fn arrow(direction: Direction, number: Option<Number>) -> Result<Self, Error> {
+        Self::new(Pointer::Go(direction), number)
+    }
+
+    #[allow(dead_code)]
+    pub fn blocked() -> Self {
+        Self {
+            pointer: Pointer::Blocked,
+            number: None,
+        }
+    }
+
+    pub fn is_blocked(self) -> bool {
+        self.pointer == Pointer::Blocked
+    }
+
+    pub fn pointer_number(self) -> Option<(Pointer, Number)> {
+        self.number.map(|n| (self.pointer, n))
+    }
+
+    #[allow(dead_code)]
+    pub fn is_checkpoint(self) -> bool {
+        self.number.is_some() && matches!(self.pointer, Pointer::Go(_))
+    }
+
+    #[allow(dead_code)]
+    pub fn with_number(self, number: Option<Number>) -> Result<Self, Error> {
+        Self::new(self.pointer, number)
+    }
+
+    #[allow(dead_code)]
+    pub fn without_number(self) -> Self {
+        Self {
+            pointer: self.pointer,
+            number: None,
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Direction {
+    type Error = &'a str;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "n" => Self::North,
+            "ne" => Self::Northeast,
+            "e" => Self::East,
+            "se" => Self::Southeast,
+            "s" => Self::South,
+            "sw" => Self::Southwest,
+            "w" => Self::West,
+            "nw" => Self::Northwest,
+            _ => return Err(value),
+        })
+    }
+}
+
+impl Direction {
+    pub fn to_unicode_arrow(self) -> &'static str {
+        match self {
+            Self::North => "⇑",
+            Self::Northeast => "⇗",
+            Self::East => "⇒",
+            Self::Southeast => "⇘",
+            Self::South => "⇓",
+            Self::Southwest => "⇙",
+            Self::West => "⇐",
+            Self::Northwest => "⇖",
+        }
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        match self {
+            Self::Northeast | Self::Southeast | Self::Southwest | Self::Northwest => true,
+            Self::North | Self::East | Self::South | Self::West => false,
+        }
+    }
+
+    pub fn is_orthogonal(self) -> bool {
+        !self.is_diagonal()
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::North => Self::South,
+            Self::Northeast => Self::Southwest,
+            Self::East => Self::West,
+            Self::Southeast => Self::Northwest,
+            Self::South => Self::North,
+            Self::Southwest => Self::Northeast,
+            Self::West => Self::East,
+            Self::Northwest => Self::Southeast,
+        }
+    }
+}
+
+// Boards are compared by dimensions first so that boards of different shapes never interleave,
+// then cell by cell in row-major order.
+#[allow(dead_code)]
+pub fn sort_boards(boards: &mut [Board]) {
+    boards
+        .sort_by_cached_key(|board| (board.num_rows(), board.num_columns(), board_sort_key(board)));
+}
+
+#[allow(dead_code)]
+pub fn number_grid(board: &Board) -> Array2D<Option<Number>> {
+    let numbers = board
+        .elements_row_major_iter()
+        .map(|cell| cell.number)
+        .collect::<Vec<_>>();
+    Array2D::from_row_major(&numbers, board.num_rows(), board.num_columns())
+        .expect("Same dimensions as board")
+}
+
+// Both boards' `to_strings` output, with `gap` spaces between each pair of rows. The shorter board
+// is padded with blank rows.
+#[allow(dead_code)]
+pub fn render_side_by_side(left: &Game, right: &Game, gap: usize) -> String {
+    let left = left.to_strings();
+    let right = right.to_strings();
+    let left_width = left
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    (0..left.len().max(right.len()))
+        .map(|i| {
+            let left_line = left.get(i).map(String::as_str).unwrap_or("");
+            let right_line = right.get(i).map(String::as_str).unwrap_or("");
+            let padding = left_width - left_line.chars().count() + gap;
+            let line = format!("{}{}{}", left_line, " ".repeat(padding), right_line);
+            line.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn board_sort_key(board: &Board) -> Vec<(Pointer, Option<Number>)> {
+    board
+        .elements_row_major_iter()
+        .map(|cell| (cell.pointer, cell.number))
+        .collect()
+}
+
+fn validate_number_domain(board: &Board) -> Result<(), Error> {
+    let max_number = chain_length(board);
+    if max_number == 0 {
+        return Err(Error::EmptyBoard);
+    }
+    // With only one cell in the chain it must be the final cell, even without a number
+    if max_number == 1 {
+        let only = board
+            .elements_row_major_iter()
+            .find(|cell| !cell.is_blocked())
+            .expect("Chain has one cell");
+        if let Pointer::Go(direction) = only.pointer {
+            return Err(Error::FinalNumberWithDirection(1, direction));
+        }
+    }
+    let mut seen = HashSet::new();
+
+    for (pointer, number) in board
+        .elements_row_major_iter()
+        .flat_map(|cell| cell.pointer_number())
+    {
+        match pointer {
+            Pointer::Go(direction) => {
+                if number == max_number {
+                    return Err(Error::FinalNumberWithDirection(number, direction));
+                }
+            }
+            Pointer::Final => {
+                if number != max_number {
+                    return Err(Error::WrongFinalNumber {
+                        actual: number,
+                        expected: max_number,
+                    });
+                }
+            }
+            Pointer::Blocked => return Err(Error::NumberOnBlockedCell(number)),
+        };
+
+        if seen.contains(&number) {
+            return Err(Error::MultipleOfNumber(number));
+        }
+        if number > max_number {
+            return Err(Error::NumberTooHigh(number));
+        }
+
+        seen.insert(number);
+    }
+
+    Ok(())
+}
+
+// Used by the `board!` macros
+#[allow(dead_code)]
+pub fn board_from_tokens(rows: Vec<Vec<(Pointer, Option<Number>)>>) -> Result<Board, Error> {
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|(pointer, number)| Cell::new(pointer, number))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Game::check_shape(&rows)?;
+    let board = Array2D::from_rows(&rows).expect("Rows all have the same length");
+    validate_number_domain(&board)?;
+    Ok(board)
+}
+
+// How many cells are part of the chain, which is also the highest number on the board
+pub fn chain_length(board: &Board) -> usize {
+    board
+        .elements_row_major_iter()
+        .filter(|cell| !cell.is_blocked())
+        .count()
+}
+
+fn log10(num: usize) -> usize {
+    let num = num as f64;
+    let exponent = num.log10();
+    let exponent_ceil = exponent.ceil();
+    exponent_ceil as usize
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn solved_example() -> Game {
+        Game::new(
+            Array2D::from_rows(&vec![
+                vec![cell!("e", 1), cell!("e", 2), cell!("s", 4), cell!("w", 3)],
+                vec![cell!("s", 6), cell!("s", 12), cell!("w", 5), cell!("w", 11)],
+                vec![
+                    cell!("se", 14),
+                    cell!("w", 13),
+                    cell!("e", 9),
+                    cell!("n", 10),
+                ],
+                vec![cell!("e", 7), cell!("e", 15), cell!("n", 8), cell!("*", 16)],
+            ])
+            .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn valid_board() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("s")],
+            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e"), cell!("w"), cell!("*", 9)],
+        ])
+        .unwrap();
+        let result = Game::new(board);
+        println!("{:?}", result);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn empty_board() {
+        let board = Array2D::from_rows(&vec![]).unwrap();
+        let result = Game::new(board);
+        println!("{:?}", result);
+        assert_eq!(result, Err(Error::EmptyBoard));
+    }
+
+    #[test]
+    fn multiple_numbers() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("s")],
+            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e", 5), cell!("w"), cell!("*", 9)],
+        ])
+        .unwrap();
+        let result = Game::new(board);
+        println!("{:?}", result);
+        assert_eq!(result, Err(Error::MultipleOfNumber(5)));
+    }
+
+    #[test]
+    fn number_too_high() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("s")],
+            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e", 10), cell!("w"), cell!("*", 9)],
+        ])
+        .unwrap();
+        let result = Game::new(board);
+        println!("{:?}", result);
+        assert_eq!(result, Err(Error::NumberTooHigh(10)));
+    }
+
+    #[test]
+    fn number_zero() {
+        assert_eq!(
+            Cell::new(Pointer::Go(Direction::East), Some(0)),
+            Err(Error::NoZeroAllowed)
+        )
+    }
+
+    #[test]
+    fn with_number() {
+        let cell = cell!("se");
+        assert_eq!(cell.with_number(Some(0)), Err(Error::NoZeroAllowed));
+        assert_eq!(cell.with_number(Some(7)), Ok(cell!("se", 7)));
+        assert_eq!(cell!("se", 7).with_number(None), Ok(cell));
+    }
+
+    #[test]
+    fn without_number() {
+        let cell = cell!("nw", 3).without_number();
+        assert_eq!(cell.pointer, Pointer::Go(Direction::Northwest));
+        assert_eq!(cell.number, None);
+    }
+
+    #[test]
+    fn wrong_final_number() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("s")],
+            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e", 8), cell!("w"), cell!("*", 8)],
+        ])
+        .unwrap();
+        let result = Game::new(board);
+        assert_eq!(
+            result,
+            Err(Error::WrongFinalNumber {
+                actual: 8,
+                expected: 9,
+            })
+        );
+    }
+
+    #[test]
+    fn final_number_with_direction() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("s")],
+            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e", 8), cell!("w"), cell!("e", 9)],
+        ])
+        .unwrap();
+        let result = Game::new(board);
+        assert_eq!(
+            result,
+            Err(Error::FinalNumberWithDirection(9, Direction::East)),
+        );
+    }
+
+    #[test]
+    fn flat_round_trip() {
+        let game = Game::example();
+        let (cells, num_rows, num_columns) = game.to_flat();
+        assert_eq!(cells.len(), 16);
+        assert_eq!((num_rows, num_columns), (4, 4));
+        assert_eq!(Game::from_flat(cells, num_rows, num_columns), Ok(game));
+    }
+
+    #[test]
+    fn from_flat_wrong_number_of_cells() {
+        let (cells, _, _) = Game::example().to_flat();
+        assert_eq!(
+            Game::from_flat(cells, 3, 4),
+            Err(Error::WrongNumberOfCells {
+                actual: 16,
+                expected: 12,
+            })
+        );
+    }
+
+    #[test]
+    fn cell_at() {
+        let game = Game::example();
+        assert_eq!(game.cell_at(0, 0), Some(&cell!("e", 1)));
+        assert_eq!(game.cell_at(1, 2), Some(&cell!("w", 5)));
+        assert_eq!(game.cell_at(3, 3), Some(&cell!("*", 16)));
+        assert_eq!(game.cell_at(4, 0), None);
+        assert_eq!(game.cell_at(0, 4), None);
+        assert_eq!(game.cell_at(usize::MAX, usize::MAX), None);
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Game::example().numbers(), 1..=16);
+        let board =
+            Array2D::from_rows(&vec![vec![cell!("e", 1), cell!("#"), cell!("*", 2)]]).unwrap();
+        assert_eq!(Game::new(board).unwrap().numbers(), 1..=2);
+    }
+
+    #[test]
+    fn enumerate() {
+        let game = Game::example();
+        let cells = game.enumerate().collect::<Vec<_>>();
+        assert_eq!(cells.len(), 16);
+        assert_eq!(cells[0], ((0, 0), &cell!("e", 1)));
+        assert_eq!(cells[3], ((0, 3), &cell!("w", 3)));
+        assert_eq!(cells[5], ((1, 1), &cell!("s", 12)));
+        assert_eq!(cells[15], ((3, 3), &cell!("*", 16)));
+
+        let numbered = game
+            .enumerate()
+            .filter_map(|(row_column, cell)| Some((row_column, cell.number?)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            numbered,
+            vec![
+                ((0, 0), 1),
+                ((0, 3), 3),
+                ((1, 1), 12),
+                ((1, 2), 5),
+                ((3, 3), 16)
+            ]
+        );
+    }
+
+    #[test]
+    fn solution_path() {
+        assert_eq!(Game::example().solution_path(), None);
+
+        let path = solved_example().solution_path().unwrap();
+        assert_eq!(path.len(), 16);
+        assert_eq!(&path[..4], &[(0, 0), (0, 1), (0, 3), (0, 2)]);
+        assert_eq!(path[15], (3, 3));
+    }
+
+    #[test]
+    fn summary() {
+        assert_eq!(
+            Game::example().summary(),
+            "4x4, 5 clues, start (0,0), final (3,3)"
+        );
+        let game = Game::example()
+            .with_cell(0, 0, cell!("e"))
+            .unwrap()
+            .with_cell(0, 1, cell!("e", 2))
+            .unwrap();
+        assert_eq!(game.summary(), "4x4, 5 clues, start unknown, final (3,3)");
+    }
+
+    #[test]
+    fn prefix_path() {
+        let game = solved_example();
+        assert_eq!(game.prefix_path(3), Some(vec![(0, 0), (0, 1), (0, 3)]));
+        assert_eq!(game.prefix_path(0), Some(vec![]));
+        assert_eq!(game.prefix_path(16), game.solution_path());
+        assert_eq!(game.prefix_path(17), None);
+        assert_eq!(Game::example().prefix_path(3), None);
+    }
+
+    #[test]
+    fn solution_directions() {
+        assert_eq!(Game::example().solution_directions(), None);
+
+        let directions = solved_example().solution_directions().unwrap();
+        assert_eq!(directions.len(), 15);
+        assert_eq!(
+            &directions[..4],
+            &[
+                Direction::East,
+                Direction::East,
+                Direction::West,
+                Direction::South
+            ]
+        );
+    }
+
+    #[test]
+    fn solution_directions_broken_chain() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e", 2), cell!("s", 3)],
+            vec![cell!("se", 6), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e", 8), cell!("e", 7), cell!("*", 9)],
+        ])
+        .unwrap();
+        let game = Game::new(board).unwrap();
+        assert_eq!(game.solution_directions(), None);
+    }
+
+    #[test]
+    fn is_checkpoint() {
+        assert!(cell!("e", 3).is_checkpoint());
+        assert!(!cell!("e").is_checkpoint());
+        assert!(!cell!("*", 16).is_checkpoint());
+    }
+
+    #[test]
+    fn checkpoints() {
+        assert_eq!(
+            Game::example().checkpoints(),
+            vec![((0, 0), 1), ((0, 3), 3), ((1, 2), 5), ((1, 1), 12)]
+        );
+    }
+
+    #[test]
+    fn into_iter() {
+        let game = Game::example();
+        let mut cells = Vec::new();
+        for cell in &game {
+            cells.push(*cell);
+        }
+        assert_eq!(cells.len(), game.board.num_elements());
+        assert_eq!(cells, game.board.as_row_major());
+        assert_eq!(cells[0], cell!("e", 1));
+        assert_eq!(cells[15], cell!("*", 16));
+    }
+
+    #[test]
+    fn apply_path() {
+        let solved = solved_example();
+        let path = solved.solution_path().unwrap();
+        let board = Game::apply_path(&Game::example().board, &path).unwrap();
+        assert_eq!(board, solved.board);
+        assert_eq!(Game::new(board).unwrap().solution_path(), Some(path));
+    }
+
+    #[test]
+    fn apply_path_errors() {
+        let board = Game::example().board;
+        let path = solved_example().solution_path().unwrap();
+
+        assert_eq!(
+            Game::apply_path(&board, &path[..15]),
+            Err(Error::WrongNumberOfCells {
+                actual: 15,
+                expected: 16,
+            })
+        );
+
+        let mut out_of_bounds = path.clone();
+        out_of_bounds[1] = (0, 4);
+        assert_eq!(
+            Game::apply_path(&board, &out_of_bounds),
+            Err(Error::OutOfBounds(0, 4))
+        );
+
+        let mut swapped = path;
+        swapped.swap(1, 2);
+        assert_eq!(
+            Game::apply_path(&board, &swapped),
+            Err(Error::ClueMismatch {
+                actual: 3,
+                expected: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn to_strings() {
+        let strings = Game::example().to_strings();
+        assert_eq!(strings[0], " 1 ⇒|   ⇒|   ⇓| 3 ⇐");
+        assert_eq!(strings[3], "   ⇒|   ⇒|   ⇑|16 ☆");
+    }
+
+    #[test]
+    fn to_strings_with_width() {
+        let game = Game::example();
+        assert_eq!(game.to_strings_with_width(2), game.to_strings());
+
+        let strings = game.to_strings_with_width(4);
+        assert_eq!(strings[0], "   1 ⇒|     ⇒|     ⇓|   3 ⇐");
+        assert_eq!(strings[3], "     ⇒|     ⇒|     ⇑|  16 ☆");
+    }
+
+    #[test]
+    fn sort_key() {
+        let key = Game::example().sort_key();
+        assert_eq!(key.len(), 16);
+        assert_eq!(key[0], (Pointer::Go(Direction::East), Some(1)));
+        assert_eq!(key[15], (Pointer::Final, Some(16)));
+    }
+
+    #[test]
+    fn sort_boards() {
+        let small = Array2D::from_rows(&vec![vec![cell!("e", 1), cell!("*", 2)]]).unwrap();
+        let example = Game::example().board;
+        let solved = solved_example().board;
+        let mut boards = vec![solved.clone(), example.clone(), small.clone()];
+        super::sort_boards(&mut boards);
+        assert_eq!(boards, vec![small.clone(), example.clone(), solved.clone()]);
+
+        let mut boards = vec![example.clone(), small.clone(), solved.clone()];
+        super::sort_boards(&mut boards);
+        assert_eq!(boards, vec![small, example, solved]);
+    }
+
+    #[test]
+    fn crop() {
+        let board = Game::example().board;
+        let expected = Array2D::from_rows(&vec![
+            vec![cell!("s", 12), cell!("w", 5)],
+            vec![cell!("w"), cell!("e")],
+        ])
+        .unwrap();
+        assert_eq!(board.crop(1, 1, 2, 2), Ok(expected));
+        assert_eq!(board.crop(0, 0, 4, 4), Ok(board.clone()));
+        assert_eq!(board.crop(3, 2, 2, 2), Err(Error::OutOfBounds(4, 3)));
+        assert_eq!(board.crop(1, 1, 0, 2), Err(Error::EmptyBoard));
+    }
+
+    #[test]
+    fn pad_to_square() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("s")],
+            vec![cell!("*", 6), cell!("w"), cell!("w")],
+        ])
+        .unwrap();
+        let padded = board.pad_to_square(Cell::blocked());
+        assert_eq!(padded.num_rows(), 3);
+        assert_eq!(padded.num_columns(), 3);
+        assert_eq!(padded.crop(0, 0, 2, 3), Ok(board));
+        assert!((0..3).all(|column| padded[(2, column)].is_blocked()));
+        assert!(Game::new(padded).is_ok());
+
+        let square = Game::example().board;
+        assert_eq!(square.pad_to_square(Cell::blocked()), square);
+    }
+
+    #[test]
+    fn merge() {
+        let layout = Game::example().into_board();
+        let solved = solved_example().into_board();
+        let mut a = layout.clone();
+        let mut b = layout.clone();
+        for (i, row_column) in layout.indices_row_major().enumerate() {
+            if i % 2 == 0 {
+                a[row_column] = solved[row_column];
+            } else {
+                b[row_column] = solved[row_column];
+            }
+        }
+        assert_eq!(Board::merge(&a, &b), Ok(solved.clone()));
+        assert_eq!(Board::merge(&layout, &layout), Ok(layout.clone()));
+
+        let mut conflicting = b.clone();
+        conflicting[(1, 1)].number = Some(11);
+        assert_eq!(
+            Board::merge(&a, &conflicting),
+            Err(MergeConflict::NumberMismatch {
+                row: 1,
+                column: 1,
+                numbers: (12, 11),
+            })
+        );
+
+        let mut moved = layout.clone();
+        moved[(0, 1)].number = Some(12);
+        assert_eq!(
+            Board::merge(&layout, &moved),
+            Err(MergeConflict::DuplicateNumber(12))
+        );
+
+        let mut turned = layout.clone();
+        turned[(0, 1)].pointer = Pointer::Go(Direction::South);
+        assert_eq!(
+            Board::merge(&layout, &turned),
+            Err(MergeConflict::PointerMismatch(0, 1))
+        );
+        assert_eq!(
+            Board::merge(&layout, &layout.crop(0, 0, 2, 2).unwrap()),
+            Err(MergeConflict::DifferentDimensions)
+        );
+    }
+
+    #[test]
+    fn difficulty_estimate() {
+        let forced = solved_example();
+        assert_eq!(forced.difficulty_estimate(), 0.0);
+
+        let sparse = Game::example();
+        let estimate = sparse.difficulty_estimate();
+        assert!(estimate > forced.difficulty_estimate());
+        assert!(estimate <= 1.0);
+    }
+
+    #[test]
+    fn difficulty_band() {
+        assert_eq!(solved_example().difficulty_band(), DifficultyBand::Easy);
+        let small = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("s")],
+            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e"), cell!("w"), cell!("*", 9)],
         ])
         .unwrap();
-        let result = Game::new(board);
         assert_eq!(
-            result,
-            Err(Error::FinalNumberWithDirection(9, Direction::East)),
+            Game::new(small).unwrap().difficulty_band(),
+            DifficultyBand::Easy
+        );
+        assert_eq!(Game::example().difficulty_band(), DifficultyBand::Medium);
+        let demo = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("s"), cell!("w", 5), cell!("sw")],
+            vec![cell!("se"), cell!("se"), cell!("s"), cell!("sw")],
+            vec![cell!("ne"), cell!("e"), cell!("w"), cell!("n")],
+            vec![cell!("n"), cell!("w"), cell!("n"), cell!("*", 16)],
+        ])
+        .unwrap();
+        assert_eq!(
+            Game::new(demo).unwrap().difficulty_band(),
+            DifficultyBand::Hard
+        );
+        let sparse = crate::generate::Generator::new(5, 5, 1)
+            .generate(crate::generate::Difficulty::Hard)
+            .unwrap();
+        assert_eq!(
+            Game::new(sparse.clues).unwrap().difficulty_band(),
+            DifficultyBand::Expert
+        );
+    }
+
+    #[test]
+    fn is_solved() {
+        assert!(!Game::example().is_solved());
+        assert!(solved_example().is_solved());
+    }
+
+    #[test]
+    fn debug_grid() {
+        let game = Game::example();
+        let expected = "\
+e1,  e,   s,   w3
+s,   s12, w5,  w
+se,  w,   e,   n
+e,   e,   n,   *16";
+        assert_eq!(game.debug_grid(), expected);
+        assert_eq!(
+            parse::parse(&game.debug_grid()).map(|board| Game { board }),
+            Ok(game)
+        );
+    }
+
+    #[test]
+    fn cell_constructors() {
+        assert_eq!(Cell::final_cell(16), Ok(cell!("*", 16)));
+        assert_eq!(Cell::final_cell(0), Err(Error::NoZeroAllowed));
+        assert_eq!(Cell::arrow(Direction::Northeast, None), Ok(cell!("ne")));
+        assert_eq!(Cell::arrow(Direction::West, Some(3)), Ok(cell!("w", 3)));
+        assert_eq!(
+            Cell::arrow(Direction::West, Some(0)),
+            Err(Error::NoZeroAllowed)
+        );
+    }
+
+    #[test]
+    fn blocked_cells() {
+        assert_eq!(
+            Cell::new(Pointer::Blocked, Some(3)),
+            Err(Error::NumberOnBlockedCell(3))
+        );
+        assert!(Cell::blocked().is_blocked());
+        assert!(!cell!("e").is_blocked());
+
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("s")],
+            vec![cell!("*", 8), cell!("#"), cell!("s")],
+            vec![cell!("n"), cell!("w"), cell!("w")],
+        ])
+        .unwrap();
+        assert_eq!(chain_length(&board), 8);
+        assert!(Game::new(board.clone()).is_ok());
+
+        let mut wrong_final = board.clone();
+        wrong_final[(1, 0)] = cell!("*", 9);
+        assert_eq!(
+            Game::new(wrong_final),
+            Err(Error::WrongFinalNumber {
+                actual: 9,
+                expected: 8,
+            })
+        );
+
+        let mut numbered_blocked = board;
+        numbered_blocked[(1, 1)].number = Some(4);
+        assert_eq!(
+            Game::new(numbered_blocked),
+            Err(Error::NumberOnBlockedCell(4))
+        );
+    }
+
+    #[test]
+    fn direction_histogram() {
+        let histogram = Game::example().direction_histogram();
+        let expected = vec![
+            (Direction::North, 2),
+            (Direction::East, 5),
+            (Direction::Southeast, 1),
+            (Direction::South, 3),
+            (Direction::West, 4),
+        ]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+        assert_eq!(histogram, expected);
+    }
+
+    #[test]
+    fn check_shape() {
+        assert_eq!(Game::check_shape(&[]), Err(Error::EmptyBoard));
+        assert_eq!(Game::check_shape(&[vec![]]), Err(Error::EmptyBoard));
+        assert_eq!(
+            Game::check_shape(&[vec![cell!("e", 1), cell!("s")], vec![cell!("*", 3)]]),
+            Err(Error::RaggedRows {
+                row: 1,
+                expected: 2,
+                found: 1,
+            })
+        );
+        let rows = Game::example().board.as_rows();
+        assert_eq!(Game::check_shape(&rows), Ok(()));
+    }
+
+    #[test]
+    fn direction_is_diagonal() {
+        use Direction::*;
+        for &direction in &[North, East, South, West] {
+            assert!(direction.is_orthogonal());
+            assert!(!direction.is_diagonal());
+        }
+        for &direction in &[Northeast, Southeast, Southwest, Northwest] {
+            assert!(direction.is_diagonal());
+            assert!(!direction.is_orthogonal());
+        }
+    }
+
+    #[test]
+    fn validate_number_domain() {
+        let mut board = Game::example().into_board();
+        assert_eq!(Game::validate_number_domain(&board), Ok(()));
+
+        board[(0, 1)].number = Some(17);
+        assert_eq!(
+            Game::validate_number_domain(&board),
+            Err(Error::NumberTooHigh(17))
+        );
+
+        board[(0, 1)].number = Some(5);
+        assert_eq!(
+            Game::validate_number_domain(&board),
+            Err(Error::MultipleOfNumber(5))
+        );
+
+        board[(0, 1)].number = None;
+        board[(3, 3)].number = Some(15);
+        assert_eq!(
+            Game::validate_number_domain(&board),
+            Err(Error::WrongFinalNumber {
+                actual: 15,
+                expected: 16,
+            })
+        );
+
+        board[(3, 3)].number = Some(16);
+        board[(3, 2)].number = Some(16);
+        assert_eq!(
+            Game::validate_number_domain(&board),
+            Err(Error::FinalNumberWithDirection(16, Direction::North))
+        );
+    }
+
+    #[test]
+    fn mean_candidates() {
+        assert_eq!(solved_example().mean_candidates(), 0.0);
+        assert!(Game::example().mean_candidates() > 0.0);
+    }
+
+    #[test]
+    fn board_macro() {
+        let board = board![
+            [e 1, e, s, w 3],
+            [s, s 12, w 5, w],
+            [se, w, e, n],
+            [e, e, n, *16],
+        ];
+        assert_eq!(board, Game::example().board);
+
+        assert_eq!(
+            try_board![[e 1, #], [*4, n]],
+            Err(Error::WrongFinalNumber {
+                actual: 4,
+                expected: 3,
+            })
+        );
+        assert_eq!(
+            try_board![[e 1, s], [*3]],
+            Err(Error::RaggedRows {
+                row: 1,
+                expected: 2,
+                found: 1,
+            })
+        );
+        assert_eq!(try_board![[e 0, *2]], Err(Error::NoZeroAllowed));
+    }
+
+    #[test]
+    fn legend() {
+        let unicode = Game::legend(ArrowStyle::Unicode);
+        assert_eq!(unicode.split("  ").count(), 9);
+        for entry in &[
+            "⇑ = N",
+            "⇗ = NE",
+            "⇒ = E",
+            "⇘ = SE",
+            "⇓ = S",
+            "⇙ = SW",
+            "⇐ = W",
+            "⇖ = NW",
+            "☆ = final",
+        ] {
+            assert!(
+                unicode.contains(entry),
+                "{} missing from {}",
+                entry,
+                unicode
+            );
+        }
+
+        let token = Game::legend(ArrowStyle::Token);
+        assert!(token.starts_with("n = N  ne = NE"));
+        assert!(token.ends_with("nw = NW  * = final"));
+    }
+
+    #[test]
+    fn into_board() {
+        let game = Game::example();
+        let board = game.board().clone();
+        assert_eq!(game.into_board(), board);
+        assert_eq!(Game::new(board.clone()).unwrap().into_board(), board);
+    }
+
+    #[test]
+    fn encode_solution() {
+        let solved = solved_example();
+        let encoded = solved.encode_solution().unwrap();
+        let (start, steps) = encoded.split_at(encoded.find(':').unwrap());
+        assert_eq!(start, "0,0");
+        let steps = steps[1..].split(',').collect::<Vec<_>>();
+        assert_eq!(steps.len(), 15);
+        assert_eq!(steps[0], "e1");
+        assert!(steps.iter().all(|step| {
+            let split = step.find(|c: char| c.is_ascii_digit()).unwrap();
+            parse::direction_from_token(&step[..split]).is_some()
+                && step[split..].parse::<usize>().is_ok()
+        }));
+
+        let board = Game::example().into_board();
+        assert_eq!(
+            Game::decode_solution(&board, &encoded),
+            Ok(solved.into_board())
+        );
+
+        assert_eq!(Game::example().encode_solution(), None);
+        assert_eq!(
+            Game::decode_solution(&board, "0,0:s1"),
+            Err(Error::InvalidEncoding)
+        );
+        assert_eq!(
+            Game::decode_solution(&board, "nonsense"),
+            Err(Error::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn is_well_formed() {
+        assert!(Game::is_well_formed(Game::example().board()));
+
+        // The first arrow points off the board, so there is no solution
+        let board = Array2D::from_rows(&vec![vec![cell!("w", 1), cell!("*", 2)]]).unwrap();
+        assert!(Game::is_well_formed(&board));
+        assert_eq!(
+            Solver::solve(board),
+            Err(crate::solver::Error::ImpossibleBoard)
+        );
+
+        let board =
+            Array2D::from_rows(&vec![vec![cell!("e", 1), cell!("w", 1), cell!("*", 3)]]).unwrap();
+        assert!(!Game::is_well_formed(&board));
+        let board =
+            Array2D::from_rows(&vec![vec![cell!("e", 1), cell!("*", 2), cell!("w")]]).unwrap();
+        assert!(!Game::is_well_formed(&board));
+    }
+
+    #[test]
+    fn with_cell() {
+        let game = Game::example();
+        assert_eq!(
+            game.with_cell(0, 1, cell!("e", 12)),
+            Err(Error::MultipleOfNumber(12))
+        );
+        assert_eq!(
+            game.with_cell(4, 0, cell!("e")),
+            Err(Error::OutOfBounds(4, 0))
         );
+
+        let updated = game.with_cell(0, 1, cell!("e", 2)).unwrap();
+        assert_eq!(updated.board[(0, 1)], cell!("e", 2));
+        assert_eq!(game, Game::example());
+    }
+
+    #[test]
+    fn cells_equal() {
+        let game = Game::example();
+        let (cells, num_rows, num_columns) = game.to_flat();
+        let copy = Game::from_flat(cells, num_rows, num_columns).unwrap();
+        assert!(game.cells_equal(&copy));
+
+        assert!(!game.cells_equal(&solved_example()));
+
+        let cells = vec![cell!("s", 1), cell!("*", 2)];
+        let wide = Game::from_flat(cells.clone(), 1, 2).unwrap();
+        let tall = Game::from_flat(cells, 2, 1).unwrap();
+        assert!(!wide.cells_equal(&tall));
+    }
+
+    #[test]
+    fn number_grid() {
+        let grid = Game::example().number_grid();
+        assert_eq!(grid.num_rows(), 4);
+        assert_eq!(grid.num_columns(), 4);
+        assert_eq!(grid[(0, 0)], Some(1));
+        assert_eq!(grid[(1, 1)], Some(12));
+        assert_eq!(grid[(0, 1)], None);
+        assert_eq!(grid[(3, 3)], Some(16));
+    }
+
+    #[test]
+    fn possible_values_for_cell() {
+        let game = Game::example();
+        // Only 1 points at (0, 1), and 3 is not along its arrow
+        assert_eq!(game.possible_values_for_cell(0, 1), vec![2]);
+        // 3 and empty cells point at (0, 2), but 13 can't go there because 12 doesn't point at
+        // it, and 15 can't because 16 isn't below it
+        assert_eq!(
+            game.possible_values_for_cell(0, 2),
+            vec![4, 7, 8, 9, 10, 14]
+        );
+        assert_eq!(game.possible_values_for_cell(0, 0), vec![1]);
+        assert_eq!(game.possible_values_for_cell(4, 0), Vec::<Number>::new());
+
+        // Every possible value is consistent with the solution
+        let solved = solved_example();
+        for ((row, column), cell) in game.enumerate() {
+            let number = solved.board()[(row, column)].number.unwrap();
+            let possible = game.possible_values_for_cell(row, column);
+            assert!(possible.contains(&number), "{:?} {:?}", (row, column), cell);
+        }
+    }
+
+    #[test]
+    fn components() {
+        let game = Game::example();
+        assert_eq!(game.components().len(), 1);
+        assert_eq!(game.components()[0].len(), 16);
+
+        // Each column is its own region since every arrow goes north or south
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("s", 1), cell!("s")],
+            vec![cell!("n"), cell!("*", 4)],
+        ])
+        .unwrap();
+        let game = Game::new(board).unwrap();
+        assert_eq!(
+            game.components(),
+            vec![vec![(0, 0), (1, 0)], vec![(0, 1), (1, 1)]]
+        );
+        assert_eq!(Solver::solve(game.into_board()).ok(), None);
+
+        // Blocked cells belong to no group, but arrows still reach past them
+        let board = Array2D::from_rows(&vec![vec![
+            cell!("e", 1),
+            cell!("#"),
+            cell!("e"),
+            cell!("*", 3),
+        ]])
+        .unwrap();
+        assert_eq!(
+            Game::new(board).unwrap().components(),
+            vec![vec![(0, 0), (0, 2), (0, 3)]]
+        );
+    }
+
+    #[test]
+    fn isolated_cells() {
+        assert_eq!(Game::example().isolated_cells(), vec![]);
+
+        // Nothing points into the top row
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("s"), cell!("s")],
+            vec![cell!("e", 1), cell!("*", 4)],
+        ])
+        .unwrap();
+        let game = Game::new(board).unwrap();
+        assert_eq!(game.isolated_cells(), vec![(0, 0), (0, 1)]);
+        assert_eq!(
+            Solver::solve(game.into_board()),
+            Err(crate::solver::Error::ImpossibleBoard)
+        );
+
+        // Without a 1 clue, either of them could be the start
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("s"), cell!("s")],
+            vec![cell!("e"), cell!("*", 4)],
+        ])
+        .unwrap();
+        assert_eq!(Game::new(board).unwrap().isolated_cells(), vec![]);
+
+        // Only 2 points at (1, 1), but 3 is already placed elsewhere
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("se", 2), cell!("w", 1)],
+            vec![cell!("n", 3), cell!("s")],
+            vec![cell!("n"), cell!("*", 6)],
+        ])
+        .unwrap();
+        assert_eq!(
+            Game::new(board).unwrap().isolated_cells(),
+            vec![(1, 1), (2, 0)]
+        );
+    }
+
+    #[test]
+    fn iter_rows() {
+        let game = Game::example();
+        let first = game.iter_rows().next().unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            first,
+            vec![
+                (0, &cell!("e", 1)),
+                (1, &cell!("e")),
+                (2, &cell!("s")),
+                (3, &cell!("w", 3)),
+            ]
+        );
+        assert_eq!(game.iter_rows().count(), 4);
+        assert!(game.iter_rows().all(|row| row.count() == 4));
+    }
+
+    #[test]
+    fn to_strings_highlight() {
+        let game = Game::example();
+        let highlight = [(0, 1), (3, 3)].iter().copied().collect::<HashSet<_>>();
+        let rows = game.to_strings_highlight(&highlight, '*');
+        assert_eq!(rows[0], "  1 ⇒ |*   ⇒*|    ⇓ |  3 ⇐ ");
+        assert_eq!(rows[3], "    ⇒ |    ⇒ |    ⇑ |*16 ☆*");
+        assert!(!rows[1].contains('*'));
+        assert!(!rows[2].contains('*'));
+    }
+
+    #[test]
+    fn clue_mask() {
+        let mask = Game::example().clue_mask();
+        let clues = [(0, 0), (0, 3), (1, 1), (1, 2), (3, 3)];
+        for (row_column, &is_clue) in mask.enumerate_row_major() {
+            assert_eq!(is_clue, clues.contains(&row_column), "{:?}", row_column);
+        }
+    }
+
+    #[test]
+    fn single_cell() {
+        let board = Array2D::from_rows(&vec![vec![cell!("*", 1)]]).unwrap();
+        assert!(Game::new(board.clone()).unwrap().is_solved());
+        assert_eq!(Solver::solve(board.clone()), Ok(board));
+
+        for &number in &[None, Some(1)] {
+            let board = Array2D::from_rows(&vec![vec![Cell::new(dir!("e"), number).unwrap()]]);
+            assert_eq!(
+                Game::new(board.unwrap()),
+                Err(Error::FinalNumberWithDirection(1, Direction::East))
+            );
+        }
+    }
+
+    #[test]
+    fn predecessors() {
+        let game = Game::example();
+        assert_eq!(game.predecessors(0, 1), vec![(0, 0), (0, 3)]);
+        assert_eq!(game.predecessors(0, 0), vec![(0, 3)]);
+        assert_eq!(game.predecessors(3, 3), vec![(3, 0), (3, 1)]);
+        assert_eq!(game.predecessors(4, 0), vec![]);
+    }
+
+    #[test]
+    fn to_strings_aligned() {
+        let arrow_columns = |lines: Vec<String>| {
+            lines
+                .iter()
+                .flat_map(|line| line.split('|').map(|cell| cell.chars().count()))
+                .collect::<HashSet<_>>()
+        };
+        // The first column has both 1 and 12
+        let game = Game::example().with_cell(1, 1, cell!("s")).unwrap();
+        let game = game.with_cell(1, 0, cell!("s", 12)).unwrap();
+        assert!(arrow_columns(game.to_strings_with_width(1)).len() > 1);
+        assert_eq!(
+            arrow_columns(game.to_strings_aligned(1)),
+            vec![4].into_iter().collect()
+        );
+        assert_eq!(game.to_strings_aligned(2), game.to_strings());
+    }
+
+    #[test]
+    fn new_lenient() {
+        let mut board = Game::example().into_board();
+        board[(1, 0)].number = Some(20);
+        assert_eq!(Game::new(board.clone()), Err(Error::NumberTooHigh(20)));
+        let (game, warnings) = Game::new_lenient(board);
+        assert_eq!(game, Ok(Game::example()));
+        assert_eq!(
+            warnings,
+            vec![Warning::DroppedNumber {
+                number: 20,
+                row: 1,
+                column: 0
+            }]
+        );
+
+        let (game, warnings) = Game::new_lenient(Game::example().into_board());
+        assert_eq!(game, Ok(Game::example()));
+        assert!(warnings.is_empty());
+
+        let mut board = Game::example().into_board();
+        board[(1, 0)].number = Some(20);
+        board[(1, 1)].number = Some(1);
+        let (game, warnings) = Game::new_lenient(board);
+        assert_eq!(game, Err(Error::MultipleOfNumber(1)));
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn to_connected_ascii() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("sw", 2)],
+            vec![cell!("e", 3), cell!("*", 4)],
+        ])
+        .unwrap();
+        assert_eq!(
+            Game::new(board).unwrap().to_connected_ascii(),
+            "1-2\n /\n3-4"
+        );
+
+        let board =
+            Array2D::from_rows(&vec![vec![cell!("e", 1), cell!("*", 3), cell!("w", 2)]]).unwrap();
+        assert_eq!(Game::new(board).unwrap().to_connected_ascii(), "1-3-2");
+
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("se", 1), cell!("sw", 3)],
+            vec![cell!("s", 4), cell!("n", 2)],
+            vec![cell!("e", 5), cell!("*", 6)],
+        ])
+        .unwrap();
+        assert_eq!(
+            Game::new(board).unwrap().to_connected_ascii(),
+            "1 3\n X|\n4 2\n|\n5-6"
+        );
+
+        let board = Array2D::from_rows(&vec![vec![cell!("e", 1), cell!("e"), cell!("#")]]).unwrap();
+        assert_eq!(Game::new(board).unwrap().to_connected_ascii(), "1 . #");
+
+        // Longer steps draw through the cells they pass over
+        let drawing = " 1- 2- 4- 3
+       |
+ 6-12- 5-11
+ |  |     |
+14-13  9-10
+ |\\    |
+ 7-15- 8-16";
+        assert_eq!(solved_example().to_connected_ascii(), drawing);
+
+        let board = Array2D::from_rows(&vec![vec![cell!("*", 1)]]).unwrap();
+        assert_eq!(Game::new(board).unwrap().to_connected_ascii(), "1");
+
+        let board = Array2D::from_rows(&vec![
+            vec![
+                cell!("e", 1),
+                cell!("e", 2),
+                cell!("e", 3),
+                cell!("e", 4),
+                cell!("s", 5),
+            ],
+            vec![
+                cell!("*", 10),
+                cell!("w", 9),
+                cell!("w", 8),
+                cell!("w", 7),
+                cell!("w", 6),
+            ],
+        ])
+        .unwrap();
+        assert_eq!(
+            Game::new(board).unwrap().to_connected_ascii(),
+            " 1- 2- 3- 4- 5\n             |\n10- 9- 8- 7- 6"
+        );
+    }
+
+    #[test]
+    fn verify_solution() {
+        let game = Game::example();
+        let solved = solved_example().into_board();
+        assert_eq!(game.verify_solution(&solved), Ok(()));
+        assert_eq!(
+            game.verify_solution(game.board()),
+            Err(SolutionError::NotSolved)
+        );
+        assert_eq!(
+            solved_example().verify_solution(&game.board),
+            Err(SolutionError::ClueMismatch(0, 1))
+        );
+
+        let mut turned = solved.clone();
+        turned[(0, 1)].pointer = Pointer::Go(Direction::South);
+        assert_eq!(
+            game.verify_solution(&turned),
+            Err(SolutionError::PointerMismatch(0, 1))
+        );
+
+        // Every number is in place but 15 points away from the star
+        let mut missed = solved;
+        let at = solved_example().index_of(15).unwrap();
+        if let Pointer::Go(direction) = missed[at].pointer {
+            missed[at].pointer = Pointer::Go(direction.opposite());
+        }
+        let game = Game::new(missed.clone()).unwrap();
+        assert_eq!(
+            game.verify_solution(&missed),
+            Err(SolutionError::FinalNotReachedFromPredecessor { number: 15, at })
+        );
+    }
+
+    #[test]
+    fn render_side_by_side() {
+        let left = Game::example();
+        let right = solved_example();
+        let rendered = super::render_side_by_side(&left, &right, 3);
+        let lines = rendered.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        for ((line, left_row), right_row) in
+            lines.iter().zip(left.to_strings()).zip(right.to_strings())
+        {
+            assert_eq!(*line, format!("{}   {}", left_row, right_row));
+        }
+
+        let short =
+            Game::new(Array2D::from_rows(&vec![vec![cell!("e", 1), cell!("*", 2)]]).unwrap())
+                .unwrap();
+        let rendered = super::render_side_by_side(&short, &left, 2);
+        let lines = rendered.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            format!("{}  {}", short.to_strings()[0], left.to_strings()[0])
+        );
+        let padding = " ".repeat(short.to_strings()[0].chars().count() + 2);
+        assert_eq!(lines[1], format!("{}{}", padding, left.to_strings()[1]));
+
+        let rendered = super::render_side_by_side(&left, &short, 2);
+        assert_eq!(rendered.lines().nth(1), Some(left.to_strings()[1].as_str()));
     }
 }
@@ -0,0 +1,479 @@
+#![allow(dead_code)]
+
+use crate::game::*;
+use crate::solver::{direction_between, Solver};
+use array2d::Array2D;
+use std::fmt;
+
+pub struct Generator {
+    num_rows: usize,
+    num_columns: usize,
+    allow_diagonals: bool,
+    rng: Rng,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Puzzle {
+    pub clues: Board,
+    pub solution: Board,
+}
+
+// For authoring puzzles by hand
+pub struct BoardBuilder;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Error {
+    EmptyBoard,
+    NoPathFound,
+    WrongPathLength { actual: usize, expected: usize },
+    PathOutOfBounds(usize, usize),
+    PathRevisits(usize, usize),
+    PathNotInLine((usize, usize), (usize, usize)),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::EmptyBoard => write!(f, "the board has no cells"),
+            Error::NoPathFound => write!(f, "no path covers the whole board"),
+            Error::WrongPathLength { actual, expected } => write!(
+                f,
+                "the path has {} cells but the board has {}",
+                actual, expected
+            ),
+            Error::PathOutOfBounds(row, column) => write!(
+                f,
+                "the path leaves the board at row {}, column {}",
+                row + 1,
+                column + 1
+            ),
+            Error::PathRevisits(row, column) => write!(
+                f,
+                "the path visits row {}, column {} more than once",
+                row + 1,
+                column + 1
+            ),
+            Error::PathNotInLine(from, to) => write!(
+                f,
+                "row {}, column {} isn't in line with row {}, column {}",
+                from.0 + 1,
+                from.1 + 1,
+                to.0 + 1,
+                to.1 + 1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl BoardBuilder {
+    // The puzzle for a path: each cell points toward the next one, and only the first cell and the
+    // final cell are numbered. The path has the same requirements as in `Generator::generate_path`.
+    pub fn from_path(
+        num_rows: usize,
+        num_columns: usize,
+        path: &[(usize, usize)],
+    ) -> Result<Board, Error> {
+        let mut board = Generator::generate_path(path, num_rows, num_columns)?;
+        for &row_column in path.iter().skip(1).take(path.len().saturating_sub(2)) {
+            board[row_column] = board[row_column].without_number();
+        }
+        Ok(board)
+    }
+}
+
+// Give up on a path attempt after this many backtracks and restart with a fresh random order.
+const MAX_BACKTRACKS: usize = 10_000;
+const MAX_ATTEMPTS: usize = 100;
+
+impl Generator {
+    pub fn new(num_rows: usize, num_columns: usize, seed: u64) -> Self {
+        Self {
+            num_rows,
+            num_columns,
+            allow_diagonals: true,
+            rng: Rng::new(seed),
+        }
+    }
+
+    // When diagonals are not allowed the path, and so every arrow, only goes north, east, south, or
+    // west
+    pub fn allow_diagonals(mut self, allow_diagonals: bool) -> Self {
+        self.allow_diagonals = allow_diagonals;
+        self
+    }
+
+    pub fn generate(&mut self, difficulty: Difficulty) -> Result<Puzzle, Error> {
+        let path = self.random_path()?;
+        let solution = self.board_from_path(&path);
+        let clues = self.choose_clues(&solution, &path, difficulty);
+        Ok(Puzzle { clues, solution })
+    }
+
+    // Build the solved board for a path chosen by hand, such as a spiral. The path must visit every
+    // cell exactly once, and each step must be along a row, column, or diagonal.
+    pub fn generate_path(
+        path: &[(usize, usize)],
+        num_rows: usize,
+        num_columns: usize,
+    ) -> Result<Board, Error> {
+        if num_rows == 0 || num_columns == 0 {
+            return Err(Error::EmptyBoard);
+        }
+        let expected = num_rows * num_columns;
+        if path.len() != expected {
+            return Err(Error::WrongPathLength {
+                actual: path.len(),
+                expected,
+            });
+        }
+        let mut visited = Array2D::filled_with(false, num_rows, num_columns);
+        for &(row, column) in path {
+            match visited.get_mut(row, column) {
+                None => return Err(Error::PathOutOfBounds(row, column)),
+                Some(true) => return Err(Error::PathRevisits(row, column)),
+                Some(seen) => *seen = true,
+            }
+        }
+        if let Some(window) = path
+            .windows(2)
+            .find(|window| direction_between(window[0], window[1]).is_none())
+        {
+            return Err(Error::PathNotInLine(window[0], window[1]));
+        }
+        Ok(Self::new(num_rows, num_columns, 0).board_from_path(path))
+    }
+
+    fn board_from_path(&self, path: &[(usize, usize)]) -> Board {
+        let placeholder = Cell::new(Pointer::Final, None).unwrap();
+        let mut board = Array2D::filled_with(placeholder, self.num_rows, self.num_columns);
+        for (i, window) in path.windows(2).enumerate() {
+            let direction =
+                direction_between(window[0], window[1]).expect("Path steps must be in a line");
+            board[window[0]] = Cell::arrow(direction, Some(i + 1)).unwrap();
+        }
+        if let Some(&last) = path.last() {
+            board[last] = Cell::final_cell(path.len()).unwrap();
+        }
+        board
+    }
+
+    // Start with every number filled in and remove clues one at a time (in random order) as long
+    // as the puzzle keeps a unique solution. Easier puzzles stop removing clues earlier.
+    fn choose_clues(
+        &mut self,
+        solution: &Board,
+        path: &[(usize, usize)],
+        difficulty: Difficulty,
+    ) -> Board {
+        let num_elements = path.len();
+        let min_clues = match difficulty {
+            Difficulty::Easy => num_elements / 2,
+            Difficulty::Medium => num_elements / 3,
+            Difficulty::Hard => 0,
+        };
+
+        let mut clues = solution.clone();
+        let mut num_clues = num_elements;
+        // The final cell always keeps its number
+        let mut removable = path[..num_elements - 1].to_vec();
+        self.rng.shuffle(&mut removable);
+        for row_column in removable {
+            if num_clues <= min_clues {
+                break;
+            }
+            let cell = clues[row_column];
+            clues[row_column] = cell.without_number();
+            if Solver::count_solutions(clues.clone(), 2) == 1 {
+                num_clues -= 1;
+            } else {
+                clues[row_column] = cell;
+            }
+        }
+        clues
+    }
+
+    fn random_path(&mut self) -> Result<Vec<(usize, usize)>, Error> {
+        if self.num_rows == 0 || self.num_columns == 0 {
+            return Err(Error::EmptyBoard);
+        }
+        for _ in 0..MAX_ATTEMPTS {
+            let start = (
+                self.rng.below(self.num_rows),
+                self.rng.below(self.num_columns),
+            );
+            let mut visited = Array2D::filled_with(false, self.num_rows, self.num_columns);
+            visited[start] = true;
+            let mut path = vec![start];
+            let mut backtracks = 0;
+            if self.extend_path(&mut path, &mut visited, &mut backtracks) {
+                return Ok(path);
+            }
+        }
+        Err(Error::NoPathFound)
+    }
+
+    // Depth-first search for a path visiting every cell. Candidates are tried in order of how few
+    // onward moves they leave (Warnsdorff's rule), with ties broken randomly.
+    fn extend_path(
+        &mut self,
+        path: &mut Vec<(usize, usize)>,
+        visited: &mut Array2D<bool>,
+        backtracks: &mut usize,
+    ) -> bool {
+        if path.len() == visited.num_elements() {
+            return true;
+        }
+        let current = *path.last().unwrap();
+        let mut candidates = self.unvisited_in_line(current, visited);
+        self.rng.shuffle(&mut candidates);
+        let mut candidates = candidates
+            .into_iter()
+            .map(|next| (self.unvisited_in_line(next, visited).len(), next))
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|&(onward, _)| onward);
+
+        for (_, next) in candidates {
+            visited[next] = true;
+            path.push(next);
+            if self.extend_path(path, visited, backtracks) {
+                return true;
+            }
+            path.pop();
+            visited[next] = false;
+            *backtracks += 1;
+            if *backtracks > MAX_BACKTRACKS {
+                return false;
+            }
+        }
+        false
+    }
+
+    fn unvisited_in_line(
+        &self,
+        from: (usize, usize),
+        visited: &Array2D<bool>,
+    ) -> Vec<(usize, usize)> {
+        visited
+            .indices_row_major()
+            .filter(|&to| !visited[to])
+            .filter(|&to| match direction_between(from, to) {
+                Some(direction) => self.allow_diagonals || direction.is_orthogonal(),
+                None => false,
+            })
+            .collect()
+    }
+}
+
+// A small xorshift generator so puzzles are reproducible from a seed without extra dependencies.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Run the seed through splitmix64 so that small seeds still give well-mixed, non-zero state
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Self {
+            state: if z == 0 { 1 } else { z },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        for &difficulty in &[Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            let puzzle = Generator::new(4, 5, 42).generate(difficulty).unwrap();
+            assert!(Game::new(puzzle.clues.clone()).is_ok());
+            assert!(Game::new(puzzle.solution.clone()).is_ok());
+            assert_eq!(Solver::count_solutions(puzzle.clues.clone(), 2), 1);
+            assert_eq!(Solver::solve(puzzle.clues), Ok(puzzle.solution));
+        }
+    }
+
+    #[test]
+    fn test_generate_same_seed() {
+        let first = Generator::new(5, 5, 7).generate(Difficulty::Hard);
+        let second = Generator::new(5, 5, 7).generate(Difficulty::Hard);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_generate_empty() {
+        let result = Generator::new(0, 3, 1).generate(Difficulty::Easy);
+        assert_eq!(result, Err(Error::EmptyBoard));
+    }
+
+    #[test]
+    fn test_generate_orthogonal() {
+        let puzzle = Generator::new(5, 4, 11)
+            .allow_diagonals(false)
+            .generate(Difficulty::Medium)
+            .unwrap();
+        let has_diagonal =
+            puzzle
+                .solution
+                .elements_row_major_iter()
+                .any(|cell| match cell.pointer {
+                    Pointer::Go(direction) => direction.is_diagonal(),
+                    _ => false,
+                });
+        assert!(!has_diagonal);
+        assert_eq!(Solver::solve(puzzle.clues), Ok(puzzle.solution));
+    }
+
+    #[test]
+    fn test_board_builder_from_path() {
+        let path = vec![(0, 0), (1, 1), (0, 2), (1, 2), (0, 1), (1, 0)];
+        let board = BoardBuilder::from_path(2, 3, &path).unwrap();
+        assert_eq!(crate::parse::board_to_text(&board), "se1,sw,s\n*6,ne,nw");
+        let solved = Solver::solve(board).unwrap();
+        assert_eq!(Game::new(solved).unwrap().solution_path(), Some(path));
+
+        assert_eq!(
+            BoardBuilder::from_path(1, 1, &[(0, 0)]).unwrap()[(0, 0)],
+            Cell::final_cell(1).unwrap()
+        );
+        assert_eq!(
+            BoardBuilder::from_path(2, 2, &[(0, 0), (1, 1), (1, 0)]),
+            Err(Error::WrongPathLength {
+                actual: 3,
+                expected: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_generate_path() {
+        // Snake back and forth across the rows
+        let path = vec![
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (1, 1),
+            (1, 0),
+            (2, 0),
+            (2, 1),
+            (2, 2),
+        ];
+        let solution = Generator::generate_path(&path, 3, 3).unwrap();
+        let game = Game::new(solution.clone()).unwrap();
+        assert_eq!(game.solution_path(), Some(path.clone()));
+
+        let mut clues = solution.clone();
+        for &row_column in &path[1..path.len() - 1] {
+            clues[row_column] = clues[row_column].without_number();
+        }
+        let solved = Solver::solve(clues).unwrap();
+        assert_eq!(
+            Game::new(solved).unwrap().solution_path(),
+            Some(path.clone())
+        );
+
+        assert_eq!(
+            Generator::generate_path(&path[..8], 3, 3),
+            Err(Error::WrongPathLength {
+                actual: 8,
+                expected: 9,
+            })
+        );
+        let mut revisits = path.clone();
+        revisits[8] = (0, 0);
+        assert_eq!(
+            Generator::generate_path(&revisits, 3, 3),
+            Err(Error::PathRevisits(0, 0))
+        );
+        let mut out_of_bounds = path.clone();
+        out_of_bounds[8] = (3, 2);
+        assert_eq!(
+            Generator::generate_path(&out_of_bounds, 3, 3),
+            Err(Error::PathOutOfBounds(3, 2))
+        );
+        let mut not_in_line = path;
+        not_in_line.swap(1, 5);
+        assert_eq!(
+            Generator::generate_path(&not_in_line, 3, 3),
+            Err(Error::PathNotInLine((1, 0), (0, 2)))
+        );
+        assert_eq!(
+            Error::PathNotInLine((1, 0), (0, 2)).to_string(),
+            "row 2, column 1 isn't in line with row 1, column 3"
+        );
+    }
+
+    #[test]
+    fn test_difficulty_clues() {
+        let count_clues = |board: &Board| {
+            board
+                .elements_row_major_iter()
+                .filter(|cell| cell.number.is_some())
+                .count()
+        };
+        let easy = Generator::new(5, 5, 3).generate(Difficulty::Easy).unwrap();
+        let hard = Generator::new(5, 5, 3).generate(Difficulty::Hard).unwrap();
+        assert!(count_clues(&easy.clues) > count_clues(&hard.clues));
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::test_runner::Config::with_cases(32))]
+
+        #[test]
+        fn test_generated_boards_solve(
+            num_rows in 1..5usize,
+            num_columns in 1..5usize,
+            seed in proptest::num::u64::ANY,
+            difficulty in proptest::sample::select(vec![
+                Difficulty::Easy,
+                Difficulty::Medium,
+                Difficulty::Hard,
+            ]),
+        ) {
+            let puzzle = Generator::new(num_rows, num_columns, seed).generate(difficulty);
+            let puzzle = match puzzle {
+                Ok(puzzle) => puzzle,
+                // Some small shapes have no path through every cell
+                Err(Error::NoPathFound) => return Ok(()),
+                Err(e) => panic!("Failed to generate: {:?}", e),
+            };
+            let game = Game::new(puzzle.clues.clone()).unwrap();
+            let solution = Solver::solve(puzzle.clues).unwrap();
+            proptest::prop_assert!(game.verify_solution(&solution).is_ok());
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub mod annotations;
+pub mod game;
+pub mod generate;
+pub mod parse;
+pub mod solver;
+pub mod transform;
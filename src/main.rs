@@ -1,10 +1,74 @@
-mod game;
-mod parse;
-mod solver;
-
 use array2d::Array2D;
+use arrows::{game, generate, parse, solver};
 use game::{Cell, Direction, Game, Pointer};
+use generate::{Difficulty, Generator};
 use solver::Solver;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const GENERATE_USAGE: &str = "Usage: arrows generate [--rows N] [--cols N] \
+                              [--difficulty easy|medium|hard] [--seed N] [--orthogonal] \
+                              [--solution]";
+// Generating gets much slower as boards grow, so a typo like `--rows 1000` shouldn't run forever
+const MAX_GENERATE_SIZE: usize = 20;
+const SOLVE_USAGE: &str = "Usage: arrows solve FILE";
+
+// Everything that can go wrong in a command, with the underlying error as its source so the whole
+// chain can be printed
+#[derive(Debug)]
+enum AppError {
+    Usage(&'static str),
+    // A bad command line argument, with the usage to show after it
+    Argument(String, &'static str),
+    Generate(generate::Error),
+    Read(String, io::Error),
+    Parse(String, parse::ParseError),
+    Game(game::Error),
+    Solve(solver::Error),
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AppError::Usage(usage) => write!(f, "{}", usage),
+            AppError::Argument(message, usage) => write!(f, "{}\n{}", message, usage),
+            AppError::Generate(_) => write!(f, "could not generate a puzzle"),
+            AppError::Read(path, _) => write!(f, "could not read {}", path),
+            AppError::Parse(path, _) => write!(f, "could not parse {}", path),
+            AppError::Game(_) => write!(f, "the puzzle is not valid"),
+            AppError::Solve(_) => write!(f, "could not solve the puzzle"),
+        }
+    }
+}
+
+impl Error for AppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AppError::Usage(_) | AppError::Argument(..) => None,
+            AppError::Generate(error) => Some(error),
+            AppError::Read(_, error) => Some(error),
+            AppError::Parse(_, error) => Some(error),
+            AppError::Game(error) => Some(error),
+            AppError::Solve(error) => Some(error),
+        }
+    }
+}
+
+// The error followed by each of its causes on its own line
+fn error_chain(error: &dyn Error) -> String {
+    let mut message = format!("error: {}", error);
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(&format!("\n  caused by: {}", cause));
+        source = cause.source();
+    }
+    message
+}
 
 macro_rules! cell {
     ($direction:tt) => {
@@ -49,20 +113,135 @@ macro_rules! dir {
     ("*") => {
         Pointer::Final;
     };
+    ("#") => {
+        Pointer::Blocked;
+    };
 }
 
 fn main() {
-    let game = Game::example();
-    for row in game.to_strings() {
-        println!("{}", row);
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("generate") => match generate(&args[1..]) {
+            Ok(output) => println!("{}", output),
+            Err(error) => {
+                eprintln!("{}", error_chain(&error));
+                process::exit(1);
+            }
+        },
+        Some("solve") => match solve_file(&args[1..]) {
+            Ok(output) => println!("{}", output),
+            Err(error) => {
+                eprintln!("{}", error_chain(&error));
+                process::exit(1);
+            }
+        },
+        Some(command) => {
+            eprintln!("Unknown command: {}", command);
+            process::exit(2);
+        }
+        None => demo(),
     }
-    println!();
+}
+
+fn generate(args: &[String]) -> Result<String, AppError> {
+    let mut num_rows = 6;
+    let mut num_columns = 6;
+    let mut difficulty = Difficulty::Medium;
+    let mut seed = None;
+    let mut show_solution = false;
+    let mut allow_diagonals = true;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| generate_argument_error(format!("Missing value for {}", arg)))
+        };
+        match arg.as_str() {
+            "--rows" => num_rows = parse_size(arg, value()?)?,
+            "--cols" => num_columns = parse_size(arg, value()?)?,
+            "--seed" => seed = Some(parse_number(arg, value()?)?),
+            "--difficulty" => {
+                difficulty = match value()?.as_str() {
+                    "easy" => Difficulty::Easy,
+                    "medium" => Difficulty::Medium,
+                    "hard" => Difficulty::Hard,
+                    other => {
+                        return Err(generate_argument_error(format!(
+                            "Unknown difficulty: {}",
+                            other
+                        )))
+                    }
+                }
+            }
+            "--solution" => show_solution = true,
+            "--orthogonal" => allow_diagonals = false,
+            _ => {
+                return Err(generate_argument_error(format!(
+                    "Unknown argument: {}",
+                    arg
+                )))
+            }
+        }
+    }
+
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0)
+    });
+    let puzzle = Generator::new(num_rows, num_columns, seed)
+        .allow_diagonals(allow_diagonals)
+        .generate(difficulty)
+        .map_err(AppError::Generate)?;
+
+    let mut output = parse::board_to_text(&puzzle.clues);
+    if show_solution {
+        output.push_str("\n\n");
+        output.push_str(&parse::board_to_text(&puzzle.solution));
+    }
+    Ok(output)
+}
 
+fn solve_file(args: &[String]) -> Result<String, AppError> {
+    let path = match args {
+        [path] => path,
+        _ => return Err(AppError::Usage(SOLVE_USAGE)),
+    };
+    let text = fs::read_to_string(path).map_err(|e| AppError::Read(path.clone(), e))?;
+    let board = parse::parse(&text).map_err(|e| AppError::Parse(path.clone(), e))?;
+    Game::new(board.clone()).map_err(AppError::Game)?;
     let solved =
-        Game::new(Solver::solve(game.board).expect("No solution")).expect("Invalid solution board");
-    for row in solved.to_strings() {
-        println!("{}", row);
+        Game::new(Solver::solve(board).map_err(AppError::Solve)?).map_err(AppError::Game)?;
+    Ok(solved.to_strings().join("\n"))
+}
+
+fn generate_argument_error(message: String) -> AppError {
+    AppError::Argument(message, GENERATE_USAGE)
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, AppError> {
+    value
+        .parse()
+        .map_err(|_| generate_argument_error(format!("Invalid value for {}: {}", flag, value)))
+}
+
+fn parse_size(flag: &str, value: &str) -> Result<usize, AppError> {
+    match parse_number(flag, value)? {
+        size @ 1..=MAX_GENERATE_SIZE => Ok(size),
+        _ => Err(generate_argument_error(format!(
+            "{} must be between 1 and {}: {}",
+            flag, MAX_GENERATE_SIZE, value
+        ))),
     }
+}
+
+fn demo() {
+    let game = Game::example();
+    let solved = Game::new(Solver::solve(game.board().clone()).expect("No solution"))
+        .expect("Invalid solution board");
+    println!("{}", game::render_side_by_side(&game, &solved, 4));
     println!();
 
     let board = Array2D::from_rows(&vec![
@@ -104,12 +283,62 @@ fn main() {
 
 fn solve(rows: &[&str]) {
     let text = rows.join("\n");
-    let board =
-        parse::parse_board::<(&str, nom::error::ErrorKind)>(&text).expect("Invalid board format");
-    let solved_board = Solver::solve(board).expect("No solution");
-    let game = Game::new(solved_board).expect("Invalid solution board");
+    let game = solver::solve_text(&text).expect("Could not solve board");
     for row in game.to_strings() {
         println!("{}", row);
     }
     println!()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_error_chain() {
+        let error = AppError::Parse(
+            "puzzle.txt".to_string(),
+            parse::parse("e1,e\nx,*4").unwrap_err(),
+        );
+        assert_eq!(
+            error_chain(&error),
+            "error: could not parse puzzle.txt\n  caused by: at line 2, column 1: unrecognized cell"
+        );
+
+        let error = AppError::Solve(solver::Error::Game(game::Error::NoZeroAllowed));
+        assert_eq!(
+            error_chain(&error),
+            "error: could not solve the puzzle\n  caused by: the board is not a valid game\n  \
+             caused by: cells can't be numbered 0"
+        );
+
+        // Only argument errors come with the usage
+        let error = AppError::Generate(generate::Error::NoPathFound);
+        assert_eq!(
+            error_chain(&error),
+            "error: could not generate a puzzle\n  caused by: no path covers the whole board"
+        );
+        let error = generate(&args(&["--rows", "0"])).unwrap_err();
+        assert_eq!(
+            error_chain(&error),
+            format!(
+                "error: --rows must be between 1 and 20: 0\n{}",
+                GENERATE_USAGE
+            )
+        );
+    }
+
+    #[test]
+    fn test_generate_bad_args() {
+        assert!(generate(&args(&["--rows"])).is_err());
+        assert!(generate(&args(&["--rows", "x"])).is_err());
+        assert!(generate(&args(&["--rows", "0"])).is_err());
+        assert!(generate(&args(&["--cols", "1000"])).is_err());
+        assert!(generate(&args(&["--difficulty", "impossible"])).is_err());
+        assert!(generate(&args(&["--bogus"])).is_err());
+    }
+}
@@ -1,38 +1,829 @@
 #![allow(unused_imports, unreachable_code, dead_code, unused_variables)]
 use crate::game;
 use crate::game::{Board, Cell, Direction, Game, Pointer};
+use crate::solver::{InconsistentAnchors, Solver};
 use array2d::Array2D;
 use nom;
 use nom::bytes::complete::tag;
 use nom::error::ErrorKind;
 use nom::Finish;
 use nom::Parser;
+use std::fmt;
 
 type I<'a> = &'a str;
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ParseError {
+    Syntax {
+        line: usize,
+        column: usize,
+        kind: ErrorKind,
+    },
+    DimensionMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    NumberTooHigh {
+        value: usize,
+        max: usize,
+        row: usize,
+        column: usize,
+    },
+    OutOfBounds {
+        row: usize,
+        column: usize,
+    },
+    InvalidToken {
+        row: usize,
+        column: usize,
+        kind: ErrorKind,
+    },
+    InvalidBytes {
+        offset: usize,
+    },
+    EmptyBoard,
+}
+
+// Whether a cell's number comes after its arrow (`e1`) or before it (`1e`)
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum NumberPosition {
+    #[default]
+    Suffix,
+    Prefix,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ParseOrGameError {
+    Parse(ParseError),
+    Game(game::Error),
+    InconsistentAnchors(InconsistentAnchors),
+}
+
+impl From<ParseError> for ParseOrGameError {
+    fn from(error: ParseError) -> Self {
+        ParseOrGameError::Parse(error)
+    }
+}
+
+impl From<game::Error> for ParseOrGameError {
+    fn from(error: game::Error) -> Self {
+        ParseOrGameError::Game(error)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Syntax { line, column, kind } => write!(
+                f,
+                "at line {}, column {}: {}",
+                line,
+                column,
+                describe_kind(*kind)
+            ),
+            ParseError::DimensionMismatch { expected, found } => write!(
+                f,
+                "the header says {}x{} but the board is {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            ParseError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "at row {}: expected {} cells but found {}",
+                row + 1,
+                expected,
+                found
+            ),
+            ParseError::NumberTooHigh {
+                value,
+                max,
+                row,
+                column,
+            } => write!(
+                f,
+                "at row {}, column {}: {} is higher than the number of cells ({})",
+                row + 1,
+                column + 1,
+                value,
+                max
+            ),
+            ParseError::OutOfBounds { row, column } => {
+                write!(f, "row {}, column {} is off the board", row + 1, column + 1)
+            }
+            ParseError::InvalidToken { row, column, kind } => write!(
+                f,
+                "at row {}, column {}: {}",
+                row + 1,
+                column + 1,
+                describe_kind(*kind)
+            ),
+            ParseError::InvalidBytes { offset } => write!(f, "at byte {}: invalid data", offset),
+            ParseError::EmptyBoard => write!(f, "the board has no cells"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// What went wrong in words, since nom's names for its errors describe the parser rather than the
+// input. Rows and columns in messages count from 1.
+fn describe_kind(kind: ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::Char => "unrecognized character",
+        ErrorKind::Digit => "expected a number",
+        ErrorKind::TooLarge => "number is too large",
+        ErrorKind::Verify => "number not allowed in this cell",
+        _ => "unrecognized cell",
+    }
+}
+
+impl fmt::Display for ParseOrGameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseOrGameError::Parse(_) => write!(f, "could not parse the board"),
+            ParseOrGameError::Game(_) => write!(f, "the board is not a valid game"),
+            ParseOrGameError::InconsistentAnchors(anchors) => write!(
+                f,
+                "{} at row {}, column {} doesn't point toward {} at row {}, column {}",
+                anchors.number,
+                anchors.from.0 + 1,
+                anchors.from.1 + 1,
+                anchors.number + 1,
+                anchors.to.0 + 1,
+                anchors.to.1 + 1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseOrGameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseOrGameError::Parse(error) => Some(error),
+            ParseOrGameError::Game(error) => Some(error),
+            ParseOrGameError::InconsistentAnchors(_) => None,
+        }
+    }
+}
+
+// Parse a board, optionally preceded by a `ROWSxCOLUMNS` header line. If the header is present the
+// board must have exactly those dimensions.
+pub fn parse(text: &str) -> Result<Board, ParseError> {
+    let (body, dimensions) = header::<(&str, ErrorKind)>(text)
+        .finish()
+        .map_err(|e| syntax_error(text, e))?;
+    let (_, rows) = nom::combinator::all_consuming(rows::<(&str, ErrorKind)>)(body)
+        .finish()
+        .map_err(|e| syntax_error(text, e))?;
+    let board = board_from_rows(rows)?;
+    if let Some(expected) = dimensions {
+        let found = (board.num_rows(), board.num_columns());
+        if found != expected {
+            return Err(ParseError::DimensionMismatch { expected, found });
+        }
+    }
+    Ok(board)
+}
+
+// Like `parse`, but also check the numbers are valid for a game
+pub fn parse_game(text: &str) -> Result<Game, ParseOrGameError> {
+    Ok(Game::new(parse(text)?)?)
+}
+
+// Like `parse_game`, but also check that each pair of consecutive clues lines up
+pub fn parse_game_validated(text: &str) -> Result<Game, ParseOrGameError> {
+    let game = parse_game(text)?;
+    Solver::new(game.board().clone())
+        .validate_anchors()
+        .map_err(ParseOrGameError::InconsistentAnchors)?;
+    Ok(game)
+}
+
+// Parse the human-readable output of `Game::to_strings`: cells separated by `|`, each a
+// right-aligned number followed by a unicode arrow.
+pub fn parse_pretty(text: &str) -> Result<Board, ParseError> {
+    let (_, rows) = nom::combinator::all_consuming(pretty_rows::<(&str, ErrorKind)>)(text)
+        .finish()
+        .map_err(|e| syntax_error(text, e))?;
+    board_from_rows(rows)
+}
+
+// Parse several boards separated by blank lines, stopping at the first one that fails. Errors give
+// the index of the failing board, and line numbers are relative to the start of that board.
+pub fn parse_boards(text: &str) -> Result<Vec<Board>, (usize, ParseError)> {
+    split_boards(text)
+        .iter()
+        .enumerate()
+        .map(|(index, board_text)| parse(board_text).map_err(|e| (index, e)))
+        .collect()
+}
+
+// Like `parse_boards`, but keep going past boards that fail to parse
+pub fn parse_boards_lenient(text: &str) -> (Vec<Board>, Vec<(usize, ParseError)>) {
+    let mut boards = Vec::new();
+    let mut errors = Vec::new();
+    for (index, board_text) in split_boards(text).iter().enumerate() {
+        match parse(board_text) {
+            Ok(board) => boards.push(board),
+            Err(e) => errors.push((index, e)),
+        }
+    }
+    (boards, errors)
+}
+
+fn split_boards(text: &str) -> Vec<String> {
+    let mut boards = Vec::new();
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if !lines.is_empty() {
+                boards.push(lines.join("\n"));
+                lines.clear();
+            }
+        } else {
+            lines.push(line);
+        }
+    }
+    if !lines.is_empty() {
+        boards.push(lines.join("\n"));
+    }
+    boards
+}
+
+fn board_from_rows(rows: Vec<Vec<Cell>>) -> Result<Board, ParseError> {
+    let expected_len = match rows.first() {
+        Some(first) if !first.is_empty() => first.len(),
+        _ => return Err(ParseError::EmptyBoard),
+    };
+    if let Some((row, cells)) = rows
+        .iter()
+        .enumerate()
+        .find(|(_, cells)| cells.len() != expected_len)
+    {
+        return Err(ParseError::RaggedRow {
+            row,
+            expected: expected_len,
+            found: cells.len(),
+        });
+    }
+    let board = Array2D::from_rows(&rows).expect("Rows all have the same length");
+    // No number can be higher than the number of cells, and unlike `Game::new` this can say where
+    // the bad number is
+    let max = board.num_elements();
+    for ((row, column), cell) in board.enumerate_row_major() {
+        match cell.number {
+            Some(value) if value > max => {
+                return Err(ParseError::NumberTooHigh {
+                    value,
+                    max,
+                    row,
+                    column,
+                })
+            }
+            _ => (),
+        }
+    }
+    Ok(board)
+}
+
+fn syntax_error(text: &str, (remaining, kind): (&str, ErrorKind)) -> ParseError {
+    let consumed = &text[..text.len() - remaining.len()];
+    let line = consumed.matches('\n').count() + 1;
+    let line_start = consumed.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let column = consumed[line_start..].chars().count() + 1;
+    ParseError::Syntax { line, column, kind }
+}
+
+fn header<'a, E>(text: &'a str) -> nom::IResult<&'a str, Option<(usize, usize)>, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    nom::combinator::opt(nom::sequence::delimited(
+        nom::character::complete::multispace0,
+        nom::sequence::separated_pair(dimension, tag("x"), dimension),
+        nom::sequence::pair(
+            nom::character::complete::space0,
+            nom::character::complete::line_ending,
+        ),
+    ))(text)
+}
+
+fn dimension<'a, E>(text: &'a str) -> nom::IResult<&'a str, usize, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    let (remaining, digits) = nom::character::complete::digit1(text)?;
+    let dimension = digits
+        .parse()
+        .map_err(|_| nom::Err::Error(E::from_error_kind(text, ErrorKind::TooLarge)))?;
+    Ok((remaining, dimension))
+}
+
 pub fn parse_board<'a, E>(text: &'a str) -> Result<Board, E>
 where
     E: nom::error::ParseError<&'a str>,
 {
-    let (_, rows) = rows(text).finish()?;
-    // for (i, row) in rows.iter().enumerate() {
-    //     println!("Row {} has {} elements", i, row.len());
-    // }
-    Ok(Array2D::from_rows(&rows).expect("Parser returned but invalid board"))
+    parse_board_with_number_position(text, NumberPosition::Suffix)
+}
+
+pub fn parse_board_with_number_position<'a, E>(
+    text: &'a str,
+    position: NumberPosition,
+) -> Result<Board, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    let (_, rows) = rows_with(position, text).finish()?;
+    Array2D::from_rows(&rows).map_err(|_| E::from_error_kind(text, ErrorKind::Verify))
+}
+
+// Build a board from `(row, column, token)` entries, with every other cell set to `default`. Later
+// entries for the same cell replace earlier ones.
+pub fn board_from_sparse(
+    num_rows: usize,
+    num_columns: usize,
+    entries: &[(usize, usize, &str)],
+    default: Cell,
+) -> Result<Board, ParseError> {
+    let mut board = Array2D::filled_with(default, num_rows, num_columns);
+    for &(row, column, token) in entries {
+        let (_, cell) = nom::combinator::all_consuming(cell::<(&str, ErrorKind)>)(token)
+            .finish()
+            .map_err(|(_, kind)| ParseError::InvalidToken { row, column, kind })?;
+        *board
+            .get_mut(row, column)
+            .ok_or(ParseError::OutOfBounds { row, column })? = cell;
+    }
+    Ok(board)
+}
+
+// The dense format has exactly one character per cell, so it only holds the layout of arrows and
+// not any numbers. `n`, `e`, `s`, and `w` are the orthogonal directions; the diagonals use their
+// positions on a numeric keypad: `9` northeast, `3` southeast, `1` southwest, `7` northwest. `*`
+// is the final cell and `#` a blocked cell.
+pub fn board_from_char_grid(grid: &[&str]) -> Result<Board, ParseError> {
+    let rows = grid
+        .iter()
+        .enumerate()
+        .map(|(row, line)| {
+            line.chars()
+                .enumerate()
+                .map(|(column, c)| {
+                    let pointer = dense_char_to_pointer(c).ok_or(ParseError::Syntax {
+                        line: row + 1,
+                        column: column + 1,
+                        kind: ErrorKind::Char,
+                    })?;
+                    Ok(Cell {
+                        pointer,
+                        number: None,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    board_from_rows(rows)
+}
+
+pub fn to_dense_string(board: &Board) -> String {
+    board
+        .rows_iter()
+        .map(|row| {
+            row.map(|cell| pointer_to_dense_char(cell.pointer))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn pointer_to_dense_char(pointer: Pointer) -> char {
+    match pointer {
+        Pointer::Go(Direction::North) => 'n',
+        Pointer::Go(Direction::Northeast) => '9',
+        Pointer::Go(Direction::East) => 'e',
+        Pointer::Go(Direction::Southeast) => '3',
+        Pointer::Go(Direction::South) => 's',
+        Pointer::Go(Direction::Southwest) => '1',
+        Pointer::Go(Direction::West) => 'w',
+        Pointer::Go(Direction::Northwest) => '7',
+        Pointer::Final => '*',
+        Pointer::Blocked => '#',
+    }
+}
+
+fn dense_char_to_pointer(c: char) -> Option<Pointer> {
+    Some(match c {
+        'n' => Pointer::Go(Direction::North),
+        '9' => Pointer::Go(Direction::Northeast),
+        'e' => Pointer::Go(Direction::East),
+        '3' => Pointer::Go(Direction::Southeast),
+        's' => Pointer::Go(Direction::South),
+        '1' => Pointer::Go(Direction::Southwest),
+        'w' => Pointer::Go(Direction::West),
+        '7' => Pointer::Go(Direction::Northwest),
+        '*' => Pointer::Final,
+        '#' => Pointer::Blocked,
+        _ => return None,
+    })
+}
+
+pub fn board_to_text(board: &Board) -> String {
+    board
+        .rows_iter()
+        .map(|row| row.map(cell_to_token).collect::<Vec<_>>().join(","))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn cell_to_token(cell: &Cell) -> String {
+    let pointer = match cell.pointer {
+        Pointer::Go(direction) => direction_to_token(direction),
+        Pointer::Final => "*",
+        Pointer::Blocked => "#",
+    };
+    match cell.number {
+        Some(number) => format!("{}{}", pointer, number),
+        None => pointer.to_string(),
+    }
+}
+
+pub fn direction_from_token(token: &str) -> Option<Direction> {
+    nom::combinator::all_consuming(dir::<(&str, ErrorKind)>)(token)
+        .ok()
+        .map(|(_, direction)| direction)
+}
+
+pub fn direction_to_token(direction: Direction) -> &'static str {
+    match direction {
+        Direction::North => "n",
+        Direction::Northeast => "ne",
+        Direction::East => "e",
+        Direction::Southeast => "se",
+        Direction::South => "s",
+        Direction::Southwest => "sw",
+        Direction::West => "w",
+        Direction::Northwest => "nw",
+    }
+}
+
+pub fn board_to_csv(board: &Board) -> String {
+    board
+        .rows_iter()
+        .map(|row| {
+            row.map(|cell| csv_field(&cell_to_token(cell)))
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// No token needs quoting today, but quote anything that would break a CSV reader just in case.
+fn csv_field(text: &str) -> String {
+    if text.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// Each row as an array of cell tokens, e.g. `[["e1","sw"],["e","*4"]]`
+pub fn board_to_json(board: &Board) -> String {
+    json_array(
+        board
+            .rows_iter()
+            .map(|row| json_array(row.map(|cell| format!("\"{}\"", cell_to_token(cell))))),
+    )
+}
+
+// Everything a frontend needs for one puzzle: the clue rows as in `board_to_json`, the numbers of
+// the solution (`null` where the solution has none), and the dimensions as `[rows, columns]`
+pub fn export_puzzle_json(clue: &Board, solution: &Board) -> String {
+    let numbers = game::number_grid(solution);
+    let numbers = json_array(numbers.rows_iter().map(|row| {
+        json_array(row.map(|number| match number {
+            Some(number) => number.to_string(),
+            None => "null".to_string(),
+        }))
+    }));
+    format!(
+        "{{\"rows\":{},\"solution\":{},\"dims\":[{},{}]}}",
+        board_to_json(clue),
+        numbers,
+        clue.num_rows(),
+        clue.num_columns()
+    )
+}
+
+fn json_array(items: impl Iterator<Item = String>) -> String {
+    format!("[{}]", items.collect::<Vec<_>>().join(","))
+}
+
+// Read back the clue and solution boards from `export_puzzle_json`. The solution takes its arrows
+// from the clue board. Only the fields `export_puzzle_json` writes are understood, in that order.
+pub fn import_puzzle_json(text: &str) -> Result<(Board, Board), ParseError> {
+    let (_, (rows, numbers, dimensions)) = nom::combinator::all_consuming(puzzle_json)(text)
+        .finish()
+        .map_err(|e| syntax_error(text, e))?;
+    let clue = board_from_rows(rows)?;
+    let found = (clue.num_rows(), clue.num_columns());
+    if found != dimensions {
+        return Err(ParseError::DimensionMismatch {
+            expected: dimensions,
+            found,
+        });
+    }
+    if numbers.len() != clue.num_rows() {
+        return Err(ParseError::DimensionMismatch {
+            expected: dimensions,
+            found: (numbers.len(), clue.num_columns()),
+        });
+    }
+    let mut solution_rows = Vec::new();
+    for (row, (cells, numbers)) in clue.rows_iter().zip(numbers).enumerate() {
+        if numbers.len() != clue.num_columns() {
+            return Err(ParseError::RaggedRow {
+                row,
+                expected: clue.num_columns(),
+                found: numbers.len(),
+            });
+        }
+        let cells = cells
+            .zip(numbers)
+            .enumerate()
+            .map(|(column, (cell, number))| {
+                Cell::new(cell.pointer, number).map_err(|_| ParseError::InvalidToken {
+                    row,
+                    column,
+                    kind: ErrorKind::Verify,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        solution_rows.push(cells);
+    }
+    let solution = board_from_rows(solution_rows)?;
+    Ok((clue, solution))
+}
+
+type PuzzleJson = (Vec<Vec<Cell>>, Vec<Vec<Option<usize>>>, (usize, usize));
+
+fn puzzle_json<'a, E>(text: &'a str) -> nom::IResult<&'a str, PuzzleJson, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    let token = nom::sequence::delimited(tag("\""), cell, tag("\""));
+    let number = nom::branch::alt((
+        nom::combinator::map(dimension, Some),
+        map_to(tag("null"), None),
+    ));
+    let dimensions = nom::sequence::delimited(
+        json_token("["),
+        nom::sequence::separated_pair(dimension, json_token(","), dimension),
+        json_token("]"),
+    );
+    nom::sequence::delimited(
+        json_token("{"),
+        nom::sequence::tuple((
+            nom::sequence::terminated(
+                json_field("rows", json_list(json_list(token))),
+                json_token(","),
+            ),
+            nom::sequence::terminated(
+                json_field("solution", json_list(json_list(number))),
+                json_token(","),
+            ),
+            json_field("dims", dimensions),
+        )),
+        json_token("}"),
+    )(text)
+}
+
+fn json_token<'a, E>(
+    token: &'static str,
+) -> impl FnMut(&'a str) -> nom::IResult<&'a str, &'a str, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    nom::sequence::delimited(
+        nom::character::complete::multispace0,
+        tag(token),
+        nom::character::complete::multispace0,
+    )
+}
+
+fn json_field<'a, O, E, F>(
+    name: &'static str,
+    value: F,
+) -> impl FnMut(&'a str) -> nom::IResult<&'a str, O, E>
+where
+    E: nom::error::ParseError<&'a str>,
+    F: Parser<&'a str, O, E>,
+{
+    nom::sequence::preceded(
+        nom::sequence::tuple((
+            nom::character::complete::multispace0,
+            nom::sequence::delimited(tag("\""), tag(name), tag("\"")),
+            json_token(":"),
+        )),
+        value,
+    )
+}
+
+fn json_list<'a, O, E, F>(item: F) -> impl FnMut(&'a str) -> nom::IResult<&'a str, Vec<O>, E>
+where
+    E: nom::error::ParseError<&'a str>,
+    F: Parser<&'a str, O, E>,
+{
+    nom::sequence::delimited(
+        json_token("["),
+        nom::multi::separated_list0(json_token(","), item),
+        json_token("]"),
+    )
+}
+
+// The binary format is the number of rows and columns as varints, then one record per cell in
+// row-major order. A record is a byte with the pointer in the low four bits (the directions
+// clockwise from north as 0 to 7, then 8 for final and 9 for blocked) and `NUMBER_FLAG` set if the
+// cell has a number, followed by the number as a varint. Varints are little-endian base 128.
+const NUMBER_FLAG: u8 = 0x10;
+
+pub fn board_to_bytes(board: &Board) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_varint(&mut bytes, board.num_rows());
+    write_varint(&mut bytes, board.num_columns());
+    for cell in board.elements_row_major_iter() {
+        let pointer = pointer_to_byte(cell.pointer);
+        match cell.number {
+            Some(number) => {
+                bytes.push(pointer | NUMBER_FLAG);
+                write_varint(&mut bytes, number);
+            }
+            None => bytes.push(pointer),
+        }
+    }
+    bytes
+}
+
+pub fn board_from_bytes(bytes: &[u8]) -> Result<Board, ParseError> {
+    let mut offset = 0;
+    let num_rows = read_varint(bytes, &mut offset)?;
+    let num_columns = read_varint(bytes, &mut offset)?;
+    let num_elements = num_rows
+        .checked_mul(num_columns)
+        .filter(|&num_elements| num_elements > 0)
+        .ok_or(ParseError::InvalidBytes { offset: 0 })?;
+
+    let mut cells = Vec::new();
+    while cells.len() < num_elements {
+        let start = offset;
+        let invalid = ParseError::InvalidBytes { offset: start };
+        let byte = *bytes.get(offset).ok_or(invalid)?;
+        offset += 1;
+        let pointer = byte_to_pointer(byte & !NUMBER_FLAG).ok_or(invalid)?;
+        let number = if byte & NUMBER_FLAG != 0 {
+            Some(read_varint(bytes, &mut offset)?)
+        } else {
+            None
+        };
+        cells.push(Cell::new(pointer, number).map_err(|_| invalid)?);
+    }
+    if offset != bytes.len() {
+        return Err(ParseError::InvalidBytes { offset });
+    }
+    Ok(Array2D::from_row_major(&cells, num_rows, num_columns).expect("Read every cell"))
+}
+
+fn pointer_to_byte(pointer: Pointer) -> u8 {
+    match pointer {
+        Pointer::Go(Direction::North) => 0,
+        Pointer::Go(Direction::Northeast) => 1,
+        Pointer::Go(Direction::East) => 2,
+        Pointer::Go(Direction::Southeast) => 3,
+        Pointer::Go(Direction::South) => 4,
+        Pointer::Go(Direction::Southwest) => 5,
+        Pointer::Go(Direction::West) => 6,
+        Pointer::Go(Direction::Northwest) => 7,
+        Pointer::Final => 8,
+        Pointer::Blocked => 9,
+    }
+}
+
+fn byte_to_pointer(byte: u8) -> Option<Pointer> {
+    Some(match byte {
+        0 => Pointer::Go(Direction::North),
+        1 => Pointer::Go(Direction::Northeast),
+        2 => Pointer::Go(Direction::East),
+        3 => Pointer::Go(Direction::Southeast),
+        4 => Pointer::Go(Direction::South),
+        5 => Pointer::Go(Direction::Southwest),
+        6 => Pointer::Go(Direction::West),
+        7 => Pointer::Go(Direction::Northwest),
+        8 => Pointer::Final,
+        9 => Pointer::Blocked,
+        _ => return None,
+    })
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], offset: &mut usize) -> Result<usize, ParseError> {
+    let start = *offset;
+    let mut value: usize = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes
+            .get(*offset)
+            .ok_or(ParseError::InvalidBytes { offset: start })?;
+        *offset += 1;
+        let low_bits = (byte & 0x7f) as usize;
+        if shift >= usize::BITS || (low_bits << shift) >> shift != low_bits {
+            return Err(ParseError::InvalidBytes { offset: start });
+        }
+        value |= low_bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+pub fn board_from_csv<'a, E>(text: &'a str) -> Result<Board, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    let (_, rows) = nom::combinator::all_consuming(nom::multi::separated_list1(
+        nom::character::complete::line_ending,
+        nom::multi::separated_list1(tag(","), csv_cell),
+    ))(text)
+    .finish()?;
+    Array2D::from_rows(&rows).map_err(|_| E::from_error_kind(text, ErrorKind::Verify))
+}
+
+// Tokens never contain quotes, so a quoted field must hold exactly one token.
+fn csv_cell<'a, E>(text: &'a str) -> nom::IResult<&'a str, Cell, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    nom::branch::alt((nom::sequence::delimited(tag("\""), cell, tag("\"")), cell))(text)
 }
 
 fn rows<'a, E>(text: &'a str) -> nom::IResult<&'a str, Vec<Vec<Cell>>, E>
 where
     E: nom::error::ParseError<&'a str>,
 {
-    nom::multi::separated_list1(nom::character::complete::line_ending, row)(text)
+    rows_with(NumberPosition::Suffix, text)
+}
+
+fn rows_with<'a, E>(
+    position: NumberPosition,
+    text: &'a str,
+) -> nom::IResult<&'a str, Vec<Vec<Cell>>, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    // Blank lines before and after the board are ignored
+    nom::sequence::delimited(
+        nom::character::complete::multispace0,
+        nom::multi::separated_list1(nom::character::complete::line_ending, |text| {
+            row_with(position, text)
+        }),
+        nom::character::complete::multispace0,
+    )(text)
 }
 
 fn row<'a, E>(text: &'a str) -> nom::IResult<&'a str, Vec<Cell>, E>
 where
     E: nom::error::ParseError<&'a str>,
 {
-    nom::multi::separated_list1(comma, cell)(text)
+    row_with(NumberPosition::Suffix, text)
+}
+
+fn row_with<'a, E>(position: NumberPosition, text: &'a str) -> nom::IResult<&'a str, Vec<Cell>, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    nom::sequence::delimited(
+        nom::character::complete::space0,
+        nom::multi::separated_list1(comma, |text| cell_with(position, text)),
+        nom::sequence::pair(
+            nom::combinator::opt(comma),
+            nom::character::complete::space0,
+        ),
+    )(text)
 }
 
 fn comma<'a, E>(text: &'a str) -> nom::IResult<&'a str, (), E>
@@ -53,19 +844,31 @@ fn cell<'a, E>(text: &'a str) -> nom::IResult<&'a str, Cell, E>
 where
     E: nom::error::ParseError<&'a str>,
 {
-    let (remaining, (pointer, number)) = nom::sequence::tuple((
-        pointer,
-        nom::combinator::opt(nom::character::complete::digit1),
-    ))(text)?;
+    cell_with(NumberPosition::Suffix, text)
+}
+
+fn cell_with<'a, E>(position: NumberPosition, text: &'a str) -> nom::IResult<&'a str, Cell, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    let number = nom::combinator::opt(nom::character::complete::digit1);
+    let (remaining, (pointer, number)) = match position {
+        NumberPosition::Suffix => nom::sequence::tuple((pointer, number))(text)?,
+        NumberPosition::Prefix => nom::combinator::map(
+            nom::sequence::tuple((number, pointer)),
+            |(number, pointer)| (pointer, number),
+        )(text)?,
+    };
     let number = match number {
+        // digit1 only matches digits, so the only way parsing can fail is overflow
         Some(s) => Some(
             s.parse()
-                .map_err(|_| nom::Err::Error(E::from_error_kind(text, ErrorKind::Digit)))?,
+                .map_err(|_| nom::Err::Error(E::from_error_kind(text, ErrorKind::TooLarge)))?,
         ),
         None => None,
     };
     let cell = Cell::new(pointer, number)
-        .map_err(|_| nom::Err::Error(E::from_error_kind(text, ErrorKind::Digit)))?;
+        .map_err(|_| nom::Err::Error(E::from_error_kind(text, ErrorKind::Verify)))?;
     Ok((remaining, cell))
 }
 
@@ -75,6 +878,7 @@ where
 {
     nom::branch::alt((
         nom::combinator::map(tag("*"), |_| Pointer::Final),
+        nom::combinator::map(tag("#"), |_| Pointer::Blocked),
         nom::combinator::map(dir, |d| Pointer::Go(d)),
     ))(text)
 }
@@ -105,6 +909,65 @@ where
     // ))(text)
 }
 
+fn pretty_rows<'a, E>(text: &'a str) -> nom::IResult<&'a str, Vec<Vec<Cell>>, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    nom::sequence::terminated(
+        nom::multi::separated_list1(
+            nom::character::complete::line_ending,
+            nom::multi::separated_list1(tag("|"), pretty_cell),
+        ),
+        nom::combinator::opt(nom::character::complete::line_ending),
+    )(text)
+}
+
+fn pretty_cell<'a, E>(text: &'a str) -> nom::IResult<&'a str, Cell, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    let (remaining, (number, pointer)) = nom::sequence::delimited(
+        nom::character::complete::space0,
+        nom::sequence::separated_pair(
+            nom::combinator::opt(nom::character::complete::digit1),
+            nom::character::complete::space0,
+            pretty_pointer,
+        ),
+        nom::character::complete::space0,
+    )(text)?;
+    let number = match number {
+        Some(s) => Some(
+            s.parse()
+                .map_err(|_| nom::Err::Error(E::from_error_kind(text, ErrorKind::TooLarge)))?,
+        ),
+        None => None,
+    };
+    let cell = Cell::new(pointer, number)
+        .map_err(|_| nom::Err::Error(E::from_error_kind(text, ErrorKind::Verify)))?;
+    Ok((remaining, cell))
+}
+
+fn pretty_pointer<'a, E>(text: &'a str) -> nom::IResult<&'a str, Pointer, E>
+where
+    E: nom::error::ParseError<&'a str>,
+{
+    let directions = [
+        Direction::North,
+        Direction::Northeast,
+        Direction::East,
+        Direction::Southeast,
+        Direction::South,
+        Direction::Southwest,
+        Direction::West,
+        Direction::Northwest,
+    ];
+    let mut pairs = vec![("☆", Pointer::Final), ("■", Pointer::Blocked)];
+    for &direction in directions.iter() {
+        pairs.push((direction.to_unicode_arrow(), Pointer::Go(direction)));
+    }
+    map_tags(pairs)(text)
+}
+
 fn map_tags<T, I, O, E>(pairs: Vec<(T, O)>) -> impl FnMut(I) -> nom::IResult<I, O, E>
 where
     T: nom::InputLength + Clone,
@@ -147,10 +1010,307 @@ mod test {
         };
 
         let actual = parse_board::<(&str, ErrorKind)>("e1,e,s,w3\ns,s12,w5,w\nse,w,e,n\ne,e,n,*16");
-        let expected = Ok(Game::example().board);
+        let expected = Ok(Game::example().into_board());
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_board_to_text() {
+        let text = "e1,e,s,w3\ns,s12,w5,w\nse,w,e,n\ne,e,n,*16";
+        assert_eq!(board_to_text(&Game::example().into_board()), text);
+        assert_eq!(
+            parse_board::<(&str, ErrorKind)>(&board_to_text(&Game::example().into_board())),
+            Ok(Game::example().into_board())
+        );
+    }
+
+    #[test]
+    fn test_export_puzzle_json() {
+        let clue = parse_board::<(&str, ErrorKind)>("e1,sw\ne,*4").unwrap();
+        let solution = Solver::solve(clue.clone()).unwrap();
+        let json = export_puzzle_json(&clue, &solution);
+        assert_eq!(
+            json,
+            r#"{"rows":[["e1","sw"],["e","*4"]],"solution":[[1,2],[3,4]],"dims":[2,2]}"#
+        );
+        assert_eq!(import_puzzle_json(&json), Ok((clue, solution)));
+
+        let clue = Game::example().into_board();
+        let solution = Solver::solve(clue.clone()).unwrap();
+        let json = export_puzzle_json(&clue, &solution);
+        assert_eq!(import_puzzle_json(&json), Ok((clue.clone(), solution)));
+        assert_eq!(
+            import_puzzle_json(&export_puzzle_json(&clue, &clue)),
+            Ok((clue.clone(), clue))
+        );
+
+        let spaced =
+            "{ \"rows\": [[\"e1\", \"sw\"], [\"e\", \"*4\"]],\n  \"solution\": [[1, null], \
+                      [null, 4]],\n  \"dims\": [2, 2] }";
+        let (clue, solution) = import_puzzle_json(spaced).unwrap();
+        assert_eq!(board_to_text(&clue), "e1,sw\ne,*4");
+        assert_eq!(board_to_text(&solution), "e1,sw\ne,*4");
+        assert!(matches!(
+            import_puzzle_json(
+                r#"{"rows":[["e1","sw"],["e","*4"]],"solution":[[1,2]],"dims":[2,2]}"#
+            ),
+            Err(ParseError::DimensionMismatch { .. })
+        ));
+        assert!(matches!(
+            import_puzzle_json(
+                r#"{"rows":[["e1","sw"],["e","*4"]],"solution":[[1,2],[3,4]],"dims":[3,2]}"#
+            ),
+            Err(ParseError::DimensionMismatch { .. })
+        ));
+        assert!(matches!(
+            import_puzzle_json(r#"{"rows":[["x"]]}"#),
+            Err(ParseError::Syntax { .. })
+        ));
+        assert_eq!(
+            import_puzzle_json(r#"{"rows":[],"solution":[],"dims":[0,0]}"#),
+            Err(ParseError::EmptyBoard)
+        );
+        assert_eq!(
+            import_puzzle_json(r#"{"rows":[[]],"solution":[[]],"dims":[1,0]}"#),
+            Err(ParseError::EmptyBoard)
+        );
+        assert_eq!(
+            import_puzzle_json(
+                r#"{"rows":[["e1","sw"],["e","*4"]],"solution":[[0,2],[3,4]],"dims":[2,2]}"#
+            ),
+            Err(ParseError::InvalidToken {
+                row: 0,
+                column: 0,
+                kind: ErrorKind::Verify
+            })
+        );
+        assert_eq!(
+            import_puzzle_json(
+                r#"{"rows":[["e1","sw"],["e","*4"]],"solution":[[1,2],[3,4,5]],"dims":[2,2]}"#
+            ),
+            Err(ParseError::RaggedRow {
+                row: 1,
+                expected: 2,
+                found: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        let board = Game::example().into_board();
+        let csv = board_to_csv(&board);
+        assert_eq!(csv, "e1,e,s,w3\ns,s12,w5,w\nse,w,e,n\ne,e,n,*16");
+        assert_eq!(board_from_csv::<(&str, ErrorKind)>(&csv), Ok(board.clone()));
+
+        let quoted = "\"e1\",e,s,w3\r\ns,s12,\"w5\",w\r\nse,w,e,n\r\ne,e,n,\"*16\"";
+        assert_eq!(board_from_csv::<(&str, ErrorKind)>(quoted), Ok(board));
+
+        assert!(board_from_csv::<(&str, ErrorKind)>("e1,e\ns").is_err());
+        assert!(board_from_csv::<(&str, ErrorKind)>("e1,\"e,s\"").is_err());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let board = Game::example().into_board();
+        let bytes = board_to_bytes(&board);
+        // A byte each for the dimensions and for each cell, and one more for each number
+        assert_eq!(bytes.len(), 2 + 16 + 5);
+        assert_eq!(board_from_bytes(&bytes), Ok(board));
+
+        let mut blocked = Game::example().into_board();
+        blocked[(1, 2)] = Cell::blocked();
+        blocked[(2, 0)].number = Some(300);
+        let bytes = board_to_bytes(&blocked);
+        assert_eq!(bytes.len(), 2 + 16 + 2 + 4);
+        assert_eq!(board_from_bytes(&bytes), Ok(blocked));
+
+        let bytes = board_to_bytes(&Game::example().into_board());
+        assert_eq!(
+            board_from_bytes(&bytes[..10]),
+            Err(ParseError::InvalidBytes { offset: 10 })
+        );
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert_eq!(
+            board_from_bytes(&extra),
+            Err(ParseError::InvalidBytes { offset: 23 })
+        );
+        let mut bad_pointer = bytes.clone();
+        bad_pointer[4] = 0x0a;
+        assert_eq!(
+            board_from_bytes(&bad_pointer),
+            Err(ParseError::InvalidBytes { offset: 4 })
+        );
+        assert_eq!(
+            board_from_bytes(&[0, 4]),
+            Err(ParseError::InvalidBytes { offset: 0 })
+        );
+        assert_eq!(
+            board_from_bytes(&[0xff; 12]),
+            Err(ParseError::InvalidBytes { offset: 0 })
+        );
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("se12"), "se12");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("a\"b"), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn test_board_trailing_separators() {
+        let c = |d| Cell {
+            pointer: Pointer::Go(d),
+            number: None,
+        };
+        let cn = |d, n| Cell {
+            pointer: Pointer::Go(d),
+            number: Some(n),
+        };
+        let expected = Ok(Array2D::from_rows(&vec![
+            vec![cn(Direction::East, 1), c(Direction::East)],
+            vec![
+                c(Direction::South),
+                Cell::new(Pointer::Final, Some(4)).unwrap(),
+            ],
+        ])
+        .unwrap());
+
+        let actual = parse_board::<(&str, ErrorKind)>("e1,e,\ns,*4");
+        assert_eq!(actual, expected);
+        let actual = parse_board::<(&str, ErrorKind)>("e1,e\ns,*4\n");
+        assert_eq!(actual, expected);
+        let actual = parse_board::<(&str, ErrorKind)>("e1,e,\ns,*4,\n");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_crlf() {
+        let text = "4x4 \r\ne1,e,s,w3 \r\ns,s12,w5,w\t\r\nse,w,e,n\r\ne,e,n,*16\r\n";
+        assert_eq!(parse(text), Ok(Game::example().into_board()));
+        let text = "e1, e, s, w3\r\ns, s12, w5, w,\r\nse, w, e, n  \r\ne, e, n, *16";
+        assert_eq!(parse(text), Ok(Game::example().into_board()));
+    }
+
+    #[test]
+    fn test_parse_indented() {
+        let text = "
+            e1,e,s,w3
+            s,s12,w5,w
+            se,w,e,n
+            e,e,n,*16
+        ";
+        assert_eq!(parse(text), Ok(Game::example().into_board()));
+        assert_eq!(
+            parse_board::<(&str, ErrorKind)>(text),
+            Ok(Game::example().into_board())
+        );
+
+        let text = "\n\n  4x4\n  e1,e,s,w3\n  s,s12,w5,w\n  se,w,e,n\n  e,e,n,*16\n\n";
+        assert_eq!(parse(text), Ok(Game::example().into_board()));
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let text = "4x4\ne1,e,s,w3\ns,s12,w5,w\nse,w,e,n\ne,e,n,*16";
+        assert_eq!(parse(text), Ok(Game::example().into_board()));
+
+        let text = "4x3\ne1,e,s,w3\ns,s12,w5,w\nse,w,e,n\ne,e,n,*16";
+        assert_eq!(
+            parse(text),
+            Err(ParseError::DimensionMismatch {
+                expected: (4, 3),
+                found: (4, 4),
+            })
+        );
+
+        let text = "e1,e,s,w3\ns,s12,w5,w\nse,w,e,n\ne,e,n,*16";
+        assert_eq!(parse(text), Ok(Game::example().into_board()));
+    }
+
+    #[test]
+    fn test_parse_error_display() {
+        assert_eq!(
+            parse("e1,e\ns,x").unwrap_err().to_string(),
+            "at line 2, column 3: unrecognized cell"
+        );
+        assert_eq!(
+            parse("x,e").unwrap_err().to_string(),
+            "at line 1, column 1: unrecognized cell"
+        );
+        assert_eq!(
+            parse("e1,e\ns").unwrap_err().to_string(),
+            "at row 2: expected 2 cells but found 1"
+        );
+        assert_eq!(
+            parse("e1,e\ns,*9").unwrap_err().to_string(),
+            "at row 2, column 2: 9 is higher than the number of cells (4)"
+        );
+        assert_eq!(
+            board_from_sparse(2, 2, &[(2, 0, "e")], Cell::blocked())
+                .unwrap_err()
+                .to_string(),
+            "row 3, column 1 is off the board"
+        );
+        assert_eq!(
+            board_from_sparse(2, 2, &[(0, 1, "e1x")], Cell::blocked())
+                .unwrap_err()
+                .to_string(),
+            "at row 1, column 2: unrecognized cell"
+        );
+        assert_eq!(
+            board_from_char_grid(&["97", "1x"]).unwrap_err().to_string(),
+            "at line 2, column 2: unrecognized character"
+        );
+        assert_eq!(
+            parse("e0").unwrap_err().to_string(),
+            "at line 1, column 1: number not allowed in this cell"
+        );
+        assert_eq!(
+            parse("#3,e1").unwrap_err().to_string(),
+            "at line 1, column 1: number not allowed in this cell"
+        );
+    }
+
+    #[test]
+    fn test_parse_syntax_error() {
+        assert_eq!(
+            parse("2x2\nx,e"),
+            Err(ParseError::Syntax {
+                line: 2,
+                column: 1,
+                kind: ErrorKind::Tag,
+            })
+        );
+        assert_eq!(
+            parse("e1,e\ns,e0"),
+            Err(ParseError::Syntax {
+                line: 2,
+                column: 3,
+                kind: ErrorKind::Eof,
+            })
+        );
+        assert_eq!(
+            parse("e1,e\ns"),
+            Err(ParseError::RaggedRow {
+                row: 1,
+                expected: 2,
+                found: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_header() {
+        let mut parser = header::<(&str, ErrorKind)>;
+        assert_eq!(parser.parse("3x12\ne"), Ok(("e", Some((3, 12)))));
+        assert_eq!(parser.parse("3x12\r\ne"), Ok(("e", Some((3, 12)))));
+        assert_eq!(parser.parse("e1,e"), Ok(("e1,e", None)));
+        assert_eq!(parser.parse("3x\ne"), Ok(("3x\ne", None)));
+    }
+
     #[test]
     fn test_row() {
         let c = |d| Cell {
@@ -168,6 +1328,10 @@ mod test {
             Ok(("", vec![c(Direction::North), c(Direction::West)]))
         );
         assert_eq!(parser.parse(""), err("", ErrorKind::Tag));
+        assert_eq!(
+            parser.parse("n,w,\ne"),
+            Ok(("\ne", vec![c(Direction::North), c(Direction::West)]))
+        );
     }
 
     #[test]
@@ -191,6 +1355,53 @@ mod test {
             Ok((", n7", cn(Direction::West, 15)))
         );
         assert_eq!(parser.parse(""), err("", ErrorKind::Tag));
+        assert_eq!(parser.parse("#"), Ok(("", Cell::blocked())));
+        assert_eq!(parser.parse("#3"), err("#3", ErrorKind::Verify));
+    }
+
+    #[test]
+    fn test_number_prefix() {
+        let parser = |text| cell_with::<(&str, ErrorKind)>(NumberPosition::Prefix, text);
+        assert_eq!(
+            parser("1e"),
+            Ok((
+                "",
+                Cell::new(Pointer::Go(Direction::East), Some(1)).unwrap()
+            ))
+        );
+        assert_eq!(
+            parser("12nw"),
+            Ok((
+                "",
+                Cell::new(Pointer::Go(Direction::Northwest), Some(12)).unwrap()
+            ))
+        );
+        assert_eq!(
+            parser("*"),
+            Ok(("", Cell::new(Pointer::Final, None).unwrap()))
+        );
+        assert_eq!(parser("0e"), err("0e", ErrorKind::Verify));
+
+        let text = "1e,e,s,3w\ns,12s,5w,w\nse,w,e,n\ne,e,n,16*";
+        assert_eq!(
+            parse_board_with_number_position::<(&str, ErrorKind)>(text, NumberPosition::Prefix),
+            Ok(Game::example().into_board())
+        );
+        assert!(parse_board_with_number_position::<(&str, ErrorKind)>(
+            text,
+            NumberPosition::Suffix
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_cell_number_too_large() {
+        let mut parser = cell;
+        assert_eq!(
+            parser.parse("e999999999999999999999"),
+            err("e999999999999999999999", ErrorKind::TooLarge)
+        );
+        assert_eq!(parser.parse("e0"), err("e0", ErrorKind::Verify));
     }
 
     #[test]
@@ -214,6 +1425,7 @@ mod test {
             Ok(("s3", Pointer::Go(Direction::South)))
         );
         assert_eq!(parser.parse("*"), Ok(("", Pointer::Final)));
+        assert_eq!(parser.parse("#"), Ok(("", Pointer::Blocked)));
         assert_eq!(parser.parse(" n"), err(" n", ErrorKind::Tag));
         assert_eq!(parser.parse(" *"), err(" *", ErrorKind::Tag));
     }
@@ -255,4 +1467,232 @@ mod test {
     ) -> Result<O, nom::Err<(I, nom::error::ErrorKind)>> {
         Err(nom::Err::Error((remaining, kind)))
     }
+
+    #[test]
+    fn test_parse_pretty() {
+        let game = Game::example();
+        let pretty = game.to_strings().join("\n");
+        assert_eq!(parse_pretty(&pretty), Ok(game.board().clone()));
+
+        let solved = crate::solver::Solver::solve(game.into_board()).unwrap();
+        let pretty = Game::new(solved.clone()).unwrap().to_strings().join("\n");
+        assert_eq!(parse_pretty(&pretty), Ok(solved));
+
+        assert_eq!(
+            parse_pretty(" 1 ⇒| ⇓\n ☆"),
+            Err(ParseError::RaggedRow {
+                row: 1,
+                expected: 2,
+                found: 1,
+            })
+        );
+        assert!(parse_pretty(" 1 e").is_err());
+    }
+
+    #[test]
+    fn test_parse_boards() {
+        let example = board_to_text(&Game::example().into_board());
+        let text = format!("{}\n\n\n2x2\ne1,s\n*4,n\n", example);
+        let boards = parse_boards(&text).unwrap();
+        assert_eq!(boards.len(), 2);
+        assert_eq!(boards[0], Game::example().into_board());
+        assert_eq!(boards[1].num_rows(), 2);
+
+        let bad = "e1,s\n*4,x";
+        let text = format!("{}\n\n{}\n\n{}", example, bad, example);
+        let error = ParseError::Syntax {
+            line: 2,
+            column: 4,
+            kind: ErrorKind::Eof,
+        };
+        assert_eq!(parse_boards(&text), Err((1, error)));
+    }
+
+    #[test]
+    fn test_parse_boards_lenient() {
+        let text = format!(
+            "{}\n\ne1,s\nzz",
+            board_to_text(&Game::example().into_board())
+        );
+        let (boards, errors) = parse_boards_lenient(&text);
+        assert_eq!(boards, vec![Game::example().into_board()]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 1);
+    }
+
+    #[test]
+    fn test_parse_game() {
+        let text = board_to_text(&Game::example().into_board());
+        assert_eq!(parse_game(&text), Ok(Game::example()));
+
+        let wrong_final = text.replace("*16", "*15");
+        assert_eq!(
+            parse_game(&wrong_final),
+            Err(ParseOrGameError::Game(game::Error::WrongFinalNumber {
+                actual: 15,
+                expected: 16,
+            }))
+        );
+        assert!(matches!(
+            parse_game("e1,x"),
+            Err(ParseOrGameError::Parse(ParseError::Syntax { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_parse_game_validated() {
+        let text = board_to_text(&Game::example().into_board());
+        assert_eq!(parse_game_validated(&text), Ok(Game::example()));
+
+        let text = "e1,e,s,w3\ns,s12,w5,w\nse,w4,e,n\ne,e,n,*16";
+        assert!(parse_game(text).is_ok());
+        assert_eq!(
+            parse_game_validated(text),
+            Err(ParseOrGameError::InconsistentAnchors(InconsistentAnchors {
+                number: 3,
+                from: (0, 3),
+                to: (2, 1),
+            }))
+        );
+        assert_eq!(
+            parse_game_validated(text).unwrap_err().to_string(),
+            "3 at row 1, column 4 doesn't point toward 4 at row 3, column 2"
+        );
+    }
+
+    #[test]
+    fn test_char_grid() {
+        let mut layout = Game::example().into_board();
+        for row_column in layout.indices_row_major().collect::<Vec<_>>() {
+            layout[row_column].number = None;
+        }
+        let dense = to_dense_string(&layout);
+        assert_eq!(dense, "eesw\nssww\n3wen\neen*");
+        let grid = dense.lines().collect::<Vec<_>>();
+        assert_eq!(board_from_char_grid(&grid), Ok(layout));
+
+        assert_eq!(
+            board_from_char_grid(&["97", "1x"]),
+            Err(ParseError::Syntax {
+                line: 2,
+                column: 2,
+                kind: ErrorKind::Char,
+            })
+        );
+        assert_eq!(
+            board_from_char_grid(&["e*", "n"]),
+            Err(ParseError::RaggedRow {
+                row: 1,
+                expected: 2,
+                found: 1,
+            })
+        );
+        assert!(board_from_char_grid(&[]).is_err());
+    }
+
+    #[test]
+    fn test_parse_number_too_high() {
+        assert_eq!(
+            parse("e1,s\ne17,*4"),
+            Err(ParseError::NumberTooHigh {
+                value: 17,
+                max: 4,
+                row: 1,
+                column: 0,
+            })
+        );
+        assert_eq!(
+            parse_pretty(" 5 ⇒| ⇓"),
+            Err(ParseError::NumberTooHigh {
+                value: 5,
+                max: 2,
+                row: 0,
+                column: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_board_from_sparse() {
+        let entries = [
+            (0, 0, "e1"),
+            (0, 3, "w3"),
+            (1, 1, "s12"),
+            (1, 2, "w5"),
+            (3, 3, "*16"),
+        ];
+        let board = board_from_sparse(4, 4, &entries, Cell::blocked()).unwrap();
+        assert_eq!(board[(0, 0)], Game::example().board()[(0, 0)]);
+        assert_eq!(board[(1, 2)], Game::example().board()[(1, 2)]);
+        assert_eq!(board[(0, 1)], Cell::blocked());
+
+        let example = Game::example().into_board();
+        let entries = example
+            .enumerate_row_major()
+            .map(|((row, column), cell)| (row, column, cell_to_token(cell)))
+            .collect::<Vec<_>>();
+        let entries = entries
+            .iter()
+            .map(|(row, column, token)| (*row, *column, token.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            board_from_sparse(4, 4, &entries, Cell::blocked()),
+            Ok(example)
+        );
+
+        assert_eq!(
+            board_from_sparse(2, 2, &[(2, 0, "e")], Cell::blocked()),
+            Err(ParseError::OutOfBounds { row: 2, column: 0 })
+        );
+        assert_eq!(
+            board_from_sparse(2, 2, &[(1, 1, "e1x")], Cell::blocked()),
+            Err(ParseError::InvalidToken {
+                row: 1,
+                column: 1,
+                kind: ErrorKind::Eof,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_board_ragged() {
+        assert_eq!(
+            parse_board::<(&str, ErrorKind)>("e1,s\n*3"),
+            Err(("e1,s\n*3", ErrorKind::Verify))
+        );
+    }
+
+    fn parse_everything(text: &str) {
+        let _ = parse_board::<(&str, ErrorKind)>(text);
+        let _ = parse_board_with_number_position::<(&str, ErrorKind)>(text, NumberPosition::Prefix);
+        let _ = board_from_csv::<(&str, ErrorKind)>(text);
+        let _ = parse(text);
+        let _ = parse_game(text);
+        let _ = parse_game_validated(text);
+        let _ = parse_pretty(text);
+        let _ = parse_boards_lenient(text);
+        let _ = board_from_bytes(text.as_bytes());
+        let _ = board_from_char_grid(&text.lines().collect::<Vec<_>>());
+        let _ = import_puzzle_json(text);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_parse_never_panics(bytes in proptest::collection::vec(proptest::num::u8::ANY, 0..64)) {
+            parse_everything(&String::from_utf8_lossy(&bytes));
+        }
+
+        // Random bytes rarely look like a board, so also try strings made of board pieces
+        #[test]
+        fn test_parse_tokens_never_panics(text in "([nesw*#|☆⇒ ,\"x]|[0-9]{1,25}|\r?\n){0,40}") {
+            parse_everything(&text);
+        }
+
+        #[test]
+        fn test_parse_json_pieces_never_panics(
+            text in r##"([{}\[\]:, ]|"rows"|"solution"|"dims"|"e1"|"sw"|"\*4"|"#"|null|[0-9]{1,3}){0,40}"##
+        ) {
+            parse_everything(&text);
+        }
+    }
 }
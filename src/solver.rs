@@ -1,17 +1,151 @@
 #![allow(dead_code, unused_variables, unreachable_patterns)]
 
+use crate::game;
 use crate::game::Direction::*;
 use crate::game::*;
+use crate::generate::Rng;
+use crate::parse;
+use array2d::Array2D;
+use std::cell::RefCell;
 use std::cmp::Ordering::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
 
 #[derive(Debug)]
 pub struct Solver {
     board: Board,
     num_to_index: HashMap<Number, Index>,
+    // The length of the chain, which only depends on the layout
+    max_number: Number,
+    // Numbers the solver filled in itself, as opposed to clues
+    placed: HashSet<Number>,
+    rays: Arc<RayCache>,
+    deadline: Option<Instant>,
+    placement_limit: Option<usize>,
+    canonical: bool,
+    candidate_order: Box<dyn OrderCandidates>,
+    strict: bool,
+    // When set, the board with the longest chain placed so far, with the last number placed
+    deepest: Option<(Number, Board)>,
+    // The search's numbers that don't have clues, from 1 up to the one being decided
+    frames: Vec<Frame>,
+    step_start: Number,
+    finished: Option<StepResult>,
+    num_placements: usize,
+    num_backtracks: usize,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+// Which cells along the previous cell's arrow the search tries first. On boards with more than one
+// solution this decides which one is found.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum CandidateOrder {
+    #[default]
+    PreferNearest,
+    PreferFarthest,
+}
+
+// Reorders the cells the search tries for each number. The cells come nearest-first along the
+// previous cell's arrow. Returned cells that weren't given or that repeat are ignored, and given cells
+// that are left out are tried afterwards in their original order, so the order never changes which
+// boards can be solved.
+pub trait OrderCandidates {
+    fn order(&self, candidates: &[(usize, usize)]) -> Vec<(usize, usize)>;
+}
+
+impl OrderCandidates for CandidateOrder {
+    fn order(&self, candidates: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        match self {
+            CandidateOrder::PreferNearest => candidates.to_vec(),
+            CandidateOrder::PreferFarthest => candidates.iter().rev().copied().collect(),
+        }
+    }
+}
+
+impl<F> OrderCandidates for F
+where
+    F: Fn(&[(usize, usize)]) -> Vec<(usize, usize)>,
+{
+    fn order(&self, candidates: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        self(candidates)
+    }
+}
+
+impl std::fmt::Debug for dyn OrderCandidates {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("OrderCandidates")
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum StepResult {
+    Placed { number: Number, at: (usize, usize) },
+    Backtracked { number: Number },
+    Solved,
+    Failed,
+}
+
+// A number the search is deciding, with the candidate cells it hasn't tried yet (the next one last)
+#[derive(Debug, Clone)]
+struct Frame {
+    number: Number,
+    candidates: Vec<Index>,
+    placed: Option<Index>,
+}
+
+// Every cell the search placed one number in, in the order they were tried, each with what the
+// search did next. A clue has a single branch at its cell.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TraceTree {
+    pub number: Number,
+    pub branches: Vec<TraceBranch>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TraceBranch {
+    pub at: (usize, usize),
+    pub clue: bool,
+    // Whether this placement is part of the solution
+    pub succeeded: bool,
+    // The next number, if the search got that far with this placement
+    pub next: Option<Box<TraceTree>>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct SearchStats {
+    pub placements: usize,
+    pub backtracks: usize,
+    pub solved: bool,
+}
+
+// Search tracing, which compiles to nothing without the `trace` feature
+macro_rules! trace_search {
+    ($level:ident, $($arg:tt)*) => {
+        #[cfg(feature = "trace")]
+        log::$level!($($arg)*);
+    };
+}
+
+// Checking the clock is comparatively expensive, so only do it every so many placements. Must be a
+// power of two.
+const DEADLINE_CHECK_INTERVAL: usize = 1024;
+
+// The trace tree grows with every placement, so `solve_traced` gives up after this many
+const TRACE_PLACEMENT_LIMIT: usize = 10_000;
+
+// `solve_min_distance` only compares this many solutions
+const MIN_DISTANCE_SOLUTION_LIMIT: usize = 10_000;
+
+// The cells along each cell's arrow only depend on the pointer layout, not on the numbers, so they
+// can be computed once and shared between solves of boards with the same layout.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RayCache {
+    pointers: Array2D<Pointer>,
+    rays: Array2D<Vec<Index>>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 struct Index {
     row: usize,
     column: usize,
@@ -20,58 +154,860 @@ struct Index {
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum Error {
     ImpossibleBoard,
+    LayoutMismatch,
+    Timeout,
+    PlacementLimit,
+    InvalidStart(usize, usize),
+    PreCheck(PreCheck),
+    Game(game::Error),
     Internal(String),
 }
 
+impl From<game::Error> for Error {
+    fn from(error: game::Error) -> Self {
+        Error::Game(error)
+    }
+}
+
+// The reasons strict validation can reject a board before searching
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PreCheck {
+    NumberDomain(game::Error),
+    DeadPointer(usize, usize),
+    // The clue after this one isn't where its arrow points
+    AnchorMismatch(Number),
+    Unreachable,
+}
+
+// The first clue of the earliest consecutive pair that don't line up, and both of their cells
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct InconsistentAnchors {
+    pub number: Number,
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SolveError {
+    Parse(parse::ParseError),
+    Game(game::Error),
+    Solver(Error),
+}
+
+impl From<parse::ParseError> for SolveError {
+    fn from(error: parse::ParseError) -> Self {
+        SolveError::Parse(error)
+    }
+}
+
+impl From<game::Error> for SolveError {
+    fn from(error: game::Error) -> Self {
+        SolveError::Game(error)
+    }
+}
+
+impl From<Error> for SolveError {
+    fn from(error: Error) -> Self {
+        SolveError::Solver(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::ImpossibleBoard => write!(f, "the board has no solution"),
+            Error::LayoutMismatch => write!(f, "the ray cache is for a different layout"),
+            Error::Timeout => write!(f, "the search ran out of time"),
+            Error::PlacementLimit => write!(f, "the search hit its placement limit"),
+            Error::InvalidStart(row, column) => {
+                write!(
+                    f,
+                    "row {}, column {} can't be the start",
+                    row + 1,
+                    column + 1
+                )
+            }
+            Error::PreCheck(_) => write!(f, "the board failed validation"),
+            Error::Game(_) => write!(f, "the board is not a valid game"),
+            Error::Internal(message) => write!(f, "internal solver error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::PreCheck(pre_check) => Some(pre_check),
+            Error::Game(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PreCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PreCheck::NumberDomain(error) => write!(f, "{}", error),
+            PreCheck::DeadPointer(row, column) => write!(
+                f,
+                "the arrow at row {}, column {} points off the board",
+                row + 1,
+                column + 1
+            ),
+            PreCheck::AnchorMismatch(number) => {
+                write!(f, "{} doesn't point toward {}", number, number + 1)
+            }
+            PreCheck::Unreachable => write!(f, "some numbers have nowhere to go"),
+        }
+    }
+}
+
+impl std::error::Error for PreCheck {}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Parse(_) => write!(f, "could not parse the board"),
+            SolveError::Game(_) => write!(f, "the board is not a valid game"),
+            SolveError::Solver(_) => write!(f, "could not solve the board"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SolveError::Parse(error) => Some(error),
+            SolveError::Game(error) => Some(error),
+            SolveError::Solver(error) => Some(error),
+        }
+    }
+}
+
+pub fn solve_text(board_text: &str) -> Result<Game, SolveError> {
+    let board = parse::parse(board_text)?;
+    Game::new(board.clone())?;
+    let solved = Solver::solve(board)?;
+    Ok(Game::new(solved)?)
+}
+
 impl Solver {
-    fn new(board: Board) -> Self {
+    pub fn new(board: Board) -> Self {
+        let rays = Arc::new(RayCache::new(&board));
+        Self::with_rays(board, rays)
+    }
+
+    fn with_rays(board: Board, rays: Arc<RayCache>) -> Self {
         let num_to_index = Self::create_num_to_index(&board);
+        let max_number = game::chain_length(&board);
         Solver {
             board,
             num_to_index,
+            max_number,
+            placed: HashSet::new(),
+            rays,
+            deadline: None,
+            placement_limit: None,
+            canonical: false,
+            candidate_order: Box::new(CandidateOrder::default()),
+            strict: false,
+            deepest: None,
+            frames: Vec::new(),
+            step_start: 1,
+            finished: None,
+            num_placements: 0,
+            num_backtracks: 0,
         }
     }
 
     pub fn solve(board: Board) -> Result<Board, Error> {
+        let rays = Arc::new(RayCache::new(&board));
+        Self::solve_with_rays(board, rays)
+    }
+
+    // Results are in the same order as `boards`. With the `rayon` feature the boards are solved in
+    // parallel.
+    #[cfg(feature = "rayon")]
+    pub fn solve_batch(boards: Vec<Board>) -> Vec<Result<Board, Error>> {
+        use rayon::prelude::*;
+        boards.into_par_iter().map(Self::solve).collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    pub fn solve_batch(boards: Vec<Board>) -> Vec<Result<Board, Error>> {
+        boards.into_iter().map(Self::solve).collect()
+    }
+
+    pub fn solve_with_rays(board: Board, rays: Arc<RayCache>) -> Result<Board, Error> {
+        if !rays.matches(&board) {
+            return Err(Error::LayoutMismatch);
+        }
+        let mut solver = Solver::with_rays(board, rays);
+        solver.solve_in_place()?;
+        Ok(solver.board)
+    }
+
+    // Solve the solver's own board, so that it can be edited through `board_mut` and re-solved
+    // with `resolve_after_edit`
+    pub fn solve_in_place(&mut self) -> Result<(), Error> {
+        if self.strict {
+            self.pre_check().map_err(Error::PreCheck)?;
+        }
+        if !self.quick_feasibility() {
+            trace_search!(debug, "Board failed the quick feasibility check");
+            return Err(Error::ImpossibleBoard);
+        }
+        let result = self.solve_internal(1);
+        trace_search!(
+            debug,
+            "Search finished with {:?} after {} placements and {} backtracks",
+            result,
+            self.num_placements,
+            self.num_backtracks
+        );
+        result
+    }
+
+    // With strict validation, solving first runs every static check and reports the first one that
+    // fails instead of a plain `ImpossibleBoard`
+    pub fn with_strict_validation(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    fn pre_check(&self) -> Result<(), PreCheck> {
+        Game::new(self.board.clone()).map_err(PreCheck::NumberDomain)?;
+        if let Some(&(row, column)) = self.dead_pointers().first() {
+            return Err(PreCheck::DeadPointer(row, column));
+        }
+        self.validate_anchors()
+            .map_err(|anchors| PreCheck::AnchorMismatch(anchors.number))?;
+        if !self.quick_feasibility() {
+            return Err(PreCheck::Unreachable);
+        }
+        Ok(())
+    }
+
+    // Every pair of consecutive clues must have the second clue along the first one's arrow
+    pub fn validate_anchors(&self) -> Result<(), InconsistentAnchors> {
+        let mut anchors = self.num_to_index.iter().collect::<Vec<_>>();
+        anchors.sort();
+        for (&number, &index) in anchors {
+            if let Some(&next_index) = self.num_to_index.get(&(number + 1)) {
+                if !self.rays.get(index).contains(&next_index) {
+                    return Err(InconsistentAnchors {
+                        number,
+                        from: index.row_column(),
+                        to: next_index.row_column(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn board_mut(&mut self) -> &mut Board {
+        &mut self.board
+    }
+
+    // After the number in `changed` was edited through `board_mut`, keep the solver's placements
+    // below the edit and only search again from the smallest number the edit could affect. If the
+    // kept placements can't lead to a solution, fall back to solving from scratch.
+    pub fn resolve_after_edit(&mut self, changed: (usize, usize)) -> Result<(), Error> {
+        let (row, column) = changed;
+        let changed_index =
+            Index::new_checked(row, column, self.board.num_rows(), self.board.num_columns())
+                .ok_or(game::Error::OutOfBounds(row, column))?;
+        let old_number = self
+            .num_to_index
+            .iter()
+            .find(|&(_, &index)| index == changed_index)
+            .map(|(&number, _)| number);
+        // The edit came from outside, so make sure it's a valid cell
+        let new_number = Cell::new(self.board[changed].pointer, self.board[changed].number)?.number;
+        if old_number == new_number {
+            return Ok(());
+        }
+
+        // A new number also constrains where the number before it points
+        let start = old_number
+            .into_iter()
+            .chain(new_number.map(|number| number - 1))
+            .min()
+            .unwrap_or(1)
+            .max(1);
+        if let Some(old_number) = old_number {
+            self.num_to_index.remove(&old_number);
+            self.placed.remove(&old_number);
+        }
+        self.clear_placed(start);
+        if let Some(new_number) = new_number {
+            if let Some(index) = self.num_to_index.insert(new_number, changed_index) {
+                // Another cell already had this number as a clue
+                self.num_to_index.insert(new_number, index);
+                return Err(Error::ImpossibleBoard);
+            }
+        }
+
+        // Searching from `start` needs every number before it to be placed already
+        let start = (1..start)
+            .find(|number| !self.num_to_index.contains_key(number))
+            .unwrap_or(start);
+        match self.solve_internal(start) {
+            Err(Error::ImpossibleBoard) if start > 1 => {
+                self.clear_placed(1);
+                self.solve_in_place()
+            }
+            result => result,
+        }
+    }
+
+    fn clear_placed(&mut self, from: Number) {
+        let cleared = self
+            .placed
+            .iter()
+            .copied()
+            .filter(|&number| number >= from)
+            .collect::<Vec<_>>();
+        for number in cleared {
+            self.placed.remove(&number);
+            if let Some(index) = self.num_to_index.remove(&number) {
+                self.board[index.row_column()].number = None;
+            }
+        }
+    }
+
+    // Solve with number 1 fixed at `start`. The start must be an unnumbered (or already 1) cell on
+    // the chain, and no other cell may already be numbered 1.
+    pub fn solve_from(mut board: Board, start: (usize, usize)) -> Result<Board, Error> {
+        let (row, column) = start;
+        match board.get(row, column) {
+            Some(cell) if !cell.is_blocked() && cell.number.unwrap_or(1) == 1 => (),
+            _ => return Err(Error::InvalidStart(row, column)),
+        }
+        let one_elsewhere = board
+            .enumerate_row_major()
+            .any(|(row_column, cell)| row_column != start && cell.number == Some(1));
+        if one_elsewhere {
+            return Err(Error::ImpossibleBoard);
+        }
+        board[start].number = Some(1);
+        Self::solve(board)
+    }
+
+    // One snapshot of the board after each number the solver fills in, in number order, ending with
+    // the full solution. Every frame is a copy of the whole board, so this is only meant for the
+    // small boards a person would watch being solved.
+    pub fn solve_frames(board: Board) -> Result<Vec<Board>, Error> {
+        let solution = Self::solve(board.clone())?;
+        let mut placed = board
+            .enumerate_row_major()
+            .filter(|(_, cell)| cell.number.is_none() && !cell.is_blocked())
+            .map(|(row_column, _)| (solution[row_column].number, row_column))
+            .collect::<Vec<_>>();
+        placed.sort();
+
+        let mut frame = board;
+        let mut frames = Vec::with_capacity(placed.len());
+        for (number, row_column) in placed {
+            frame[row_column].number = number;
+            frames.push(frame.clone());
+        }
+        Ok(frames)
+    }
+
+    // Candidates for each number are normally tried nearest-first along the previous cell's arrow.
+    // Trying them in (row, column) order instead means the first solution found is the one whose
+    // path, as a sequence of (row, column) positions, is lexicographically smallest.
+    pub fn solve_canonical(board: Board) -> Result<Board, Error> {
         let mut solver = Solver::new(board);
+        solver.canonical = true;
+        solver.solve_in_place()?;
+        Ok(solver.board)
+    }
+
+    // Solve as far as possible. If there's no solution, give the board from the branch that got
+    // furthest along the chain, along with the cells that are still empty.
+    pub fn solve_partial(board: Board) -> (Board, Vec<(usize, usize)>) {
+        let mut solver = Solver::new(board.clone());
+        solver.deepest = Some((0, board));
+        let board = match solver.solve_internal(1) {
+            Ok(()) => solver.board,
+            Err(_) => solver.deepest.expect("Set above").1,
+        };
+        let remaining = board
+            .enumerate_row_major()
+            .filter(|(_, cell)| cell.number.is_none() && !cell.is_blocked())
+            .map(|(row_column, _)| row_column)
+            .collect();
+        (board, remaining)
+    }
+
+    // `order` is either a `CandidateOrder` or a closure such as
+    // `|candidates| candidates.iter().rev().copied().collect()`
+    pub fn solve_with_order<O>(board: Board, order: O) -> Result<Board, Error>
+    where
+        O: OrderCandidates + 'static,
+    {
+        let mut solver = Solver::new(board);
+        solver.candidate_order = Box::new(order);
+        solver.solve_in_place()?;
+        Ok(solver.board)
+    }
+
+    // Try the candidates for each number in a random order, so that boards with several solutions
+    // give one that depends on the seed. The same seed always gives the same solution.
+    pub fn solve_seeded(board: Board, seed: u64) -> Result<Board, Error> {
+        let rng = RefCell::new(Rng::new(seed));
+        Self::solve_with_order(board, move |candidates: &[(usize, usize)]| {
+            let mut candidates = candidates.to_vec();
+            rng.borrow_mut().shuffle(&mut candidates);
+            candidates
+        })
+    }
+
+    pub fn solve_until(board: Board, deadline: Instant) -> Result<Board, Error> {
+        let mut solver = Solver::new(board);
+        solver.deadline = Some(deadline);
+        if !solver.quick_feasibility() {
+            return Err(Error::ImpossibleBoard);
+        }
         solver.solve_internal(1)?;
         Ok(solver.board)
     }
 
-    fn solve_internal(&mut self, number: Number) -> Result<(), Error> {
-        if number >= self.board.num_elements() {
-            return Ok(());
+    // Search for a solution, giving up after `placement_limit` placements, and report how much work
+    // the search took
+    pub fn search_stats(board: Board, placement_limit: usize) -> SearchStats {
+        let mut solver = Solver::new(board);
+        solver.placement_limit = Some(placement_limit);
+        let solved = solver.quick_feasibility() && solver.solve_internal(1).is_ok();
+        SearchStats {
+            placements: solver.num_placements,
+            backtracks: solver.num_backtracks,
+            solved,
+        }
+    }
+
+    // Solve while recording each placement the search tries, starting from number 1. Only meant for
+    // small boards, since the search gives up after `TRACE_PLACEMENT_LIMIT` placements.
+    pub fn solve_traced(board: Board) -> (Result<Board, Error>, TraceTree) {
+        let mut solver = Solver::new(board);
+        solver.placement_limit = Some(TRACE_PLACEMENT_LIMIT);
+        let mut trace = Vec::new();
+        let result = solver.solve_traced_internal(&mut trace);
+        close_trace(&mut trace, 1);
+        let root = trace.pop().unwrap_or(TraceTree {
+            number: 1,
+            branches: Vec::new(),
+        });
+        (result.map(|()| solver.board), root)
+    }
+
+    // `trace` holds the tree for each number from 1 up to the deepest one the search is on, each
+    // waiting to be attached to the last branch of the one before it
+    fn solve_traced_internal(&mut self, trace: &mut Vec<TraceTree>) -> Result<(), Error> {
+        if !self.quick_feasibility() {
+            return Err(Error::ImpossibleBoard);
+        }
+        self.frames.clear();
+        self.step_start = 1;
+        self.finished = None;
+        loop {
+            match self.try_step()? {
+                StepResult::Placed { number, at } => {
+                    self.open_trace(trace, number);
+                    trace[number - 1].branches.push(TraceBranch {
+                        at,
+                        clue: false,
+                        succeeded: false,
+                        next: None,
+                    });
+                }
+                StepResult::Backtracked { number } => {
+                    // The number after it ran out of cells, possibly without placing any
+                    self.open_trace(trace, self.next_unplaced(number + 1));
+                    close_trace(trace, number);
+                }
+                StepResult::Solved => {
+                    self.open_trace(trace, self.max_number());
+                    for tree in trace.iter_mut() {
+                        if let Some(branch) = tree.branches.last_mut() {
+                            branch.succeeded = true;
+                        }
+                    }
+                    return Ok(());
+                }
+                StepResult::Failed => {
+                    self.open_trace(trace, self.next_unplaced(1));
+                    return Err(Error::ImpossibleBoard);
+                }
+            }
         }
-        if self.num_to_index.contains_key(&number) {
-            return self.solve_internal(number + 1);
+    }
+
+    // Add trees for each number up to `number`. Clues get their one branch.
+    fn open_trace(&self, trace: &mut Vec<TraceTree>, number: Number) {
+        while trace.len() < number {
+            let number = trace.len() + 1;
+            let branches = match self.num_to_index.get(&number) {
+                Some(index) if !self.placed.contains(&number) => vec![TraceBranch {
+                    at: index.row_column(),
+                    clue: true,
+                    succeeded: false,
+                    next: None,
+                }],
+                _ => Vec::new(),
+            };
+            trace.push(TraceTree { number, branches });
         }
+    }
 
-        let prev_number = number - 1;
-        let next_number = number + 1;
-        let possible_indices = self.get_possible_indices_from_prev(prev_number)?;
-        for index in possible_indices.into_iter() {
-            let row_column = index.row_column();
-            let next_index = self.num_to_index.get(&next_number).clone();
-            if let Some(next_index) = next_index {
-                let direction = match self.board[row_column].pointer {
-                    Pointer::Go(direction) => direction,
-                    Pointer::Final => continue,
-                };
-                if get_direction(index, *next_index) != Some(direction) {
-                    continue;
+    fn next_unplaced(&self, from: Number) -> Number {
+        (from..=self.max_number())
+            .find(|number| !self.num_to_index.contains_key(number))
+            .unwrap_or_else(|| self.max_number())
+    }
+
+    fn check_limits(&mut self) -> Result<(), Error> {
+        let num_placements = self.num_placements;
+        if let Some(placement_limit) = self.placement_limit {
+            if num_placements >= placement_limit {
+                return Err(Error::PlacementLimit);
+            }
+        }
+        self.num_placements += 1;
+        match self.deadline {
+            Some(deadline)
+                if num_placements & (DEADLINE_CHECK_INTERVAL - 1) == 0
+                    && Instant::now() >= deadline =>
+            {
+                Err(Error::Timeout)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn solve_internal(&mut self, start: Number) -> Result<(), Error> {
+        self.frames.clear();
+        self.step_start = start;
+        self.finished = None;
+        loop {
+            match self.try_step()? {
+                StepResult::Solved => return Ok(()),
+                StepResult::Failed => return Err(Error::ImpossibleBoard),
+                StepResult::Placed { .. } | StepResult::Backtracked { .. } => (),
+            }
+        }
+    }
+
+    // Run the search one placement or backtrack at a time. Once the search is over this keeps
+    // returning `Solved` or `Failed`. The first step runs the same checks as `solve_in_place` before
+    // searching, so a board that fails them fails (or errors, with strict validation) right away.
+    // Running out of time or placements is an error, and stepping again after one carries on.
+    pub fn step(&mut self) -> Result<StepResult, Error> {
+        if self.frames.is_empty() && self.finished.is_none() && self.num_placements == 0 {
+            if self.strict {
+                self.pre_check().map_err(Error::PreCheck)?;
+            }
+            if !self.quick_feasibility() {
+                self.finished = Some(StepResult::Failed);
+            }
+        }
+        self.try_step()
+    }
+
+    fn try_step(&mut self) -> Result<StepResult, Error> {
+        if let Some(result) = self.finished {
+            return Ok(result);
+        }
+
+        // Move on to the next number without a cell once the current one has been placed
+        let next_from = match self.frames.last() {
+            Some(frame) if frame.placed.is_none() => None,
+            Some(frame) => Some(frame.number + 1),
+            None => Some(self.step_start),
+        };
+        if let Some(from) = next_from {
+            let number =
+                (from..=self.max_number()).find(|number| !self.num_to_index.contains_key(number));
+            let number = match number {
+                Some(number) => number,
+                None => {
+                    self.finished = Some(StepResult::Solved);
+                    return Ok(StepResult::Solved);
                 }
+            };
+            let mut candidates = self.get_possible_indices_from_prev(number - 1)?;
+            if self.canonical {
+                candidates.sort();
+            } else {
+                candidates = self.order_candidates(&candidates);
             }
+            // Tried from the back
+            candidates.reverse();
+            self.frames.push(Frame {
+                number,
+                candidates,
+                placed: None,
+            });
+        }
+
+        let frame = self.frames.last_mut().expect("Pushed above");
+        let number = frame.number;
+        let mut candidates = std::mem::take(&mut frame.candidates);
+        while let Some(index) = candidates.pop() {
+            if !self.fits_next(index, number) {
+                continue;
+            }
+            let row_column = index.row_column();
             assert!(self.board[row_column].number.is_none());
+            // Put the untried candidates back so that stepping again picks up where this left off
+            if let Err(error) = self.check_limits() {
+                candidates.push(index);
+                self.frames
+                    .last_mut()
+                    .expect("Still on the stack")
+                    .candidates = candidates;
+                return Err(error);
+            }
             self.num_to_index.insert(number, index);
+            self.placed.insert(number);
             self.board[row_column].number = Some(number);
-            if self.solve_internal(next_number).is_ok() {
-                return Ok(());
+            trace_search!(trace, "Placed {} at {:?}", number, row_column);
+            if let Some((deepest_number, _)) = self.deepest {
+                if number > deepest_number {
+                    self.deepest = Some((number, self.board.clone()));
+                }
+            }
+            let frame = self.frames.last_mut().expect("Still on the stack");
+            frame.candidates = candidates;
+            frame.placed = Some(index);
+            return Ok(StepResult::Placed {
+                number,
+                at: row_column,
+            });
+        }
+
+        // Nothing fits this number, so take back the number before it
+        self.frames.pop();
+        match self.take_back() {
+            Some(number) => {
+                self.num_backtracks += 1;
+                Ok(StepResult::Backtracked { number })
+            }
+            None => {
+                self.finished = Some(StepResult::Failed);
+                Ok(StepResult::Failed)
+            }
+        }
+    }
+
+    // Remove the placement of the number the search is deciding, so that the next step tries its
+    // next candidate
+    fn take_back(&mut self) -> Option<Number> {
+        let frame = self.frames.last_mut()?;
+        let number = frame.number;
+        let index = frame
+            .placed
+            .take()
+            .expect("Only placed frames are below the top");
+        trace_search!(
+            trace,
+            "Backtracked {} from {:?}",
+            number,
+            index.row_column()
+        );
+        self.num_to_index.remove(&number);
+        self.placed.remove(&number);
+        self.board[index.row_column()].number = None;
+        Some(number)
+    }
+
+    // Whether `number` can go at `index`, given the final cell and a clue for the number after it
+    fn fits_next(&self, index: Index, number: Number) -> bool {
+        if !self.fits_final(index, number) {
+            return false;
+        }
+        match self.num_to_index.get(&(number + 1)) {
+            Some(&next_index) => match self.board[index.row_column()].pointer {
+                Pointer::Go(direction) => get_direction(index, next_index) == Some(direction),
+                Pointer::Final | Pointer::Blocked => false,
+            },
+            None => true,
+        }
+    }
+
+    fn order_candidates(&self, candidates: &[Index]) -> Vec<Index> {
+        let row_columns = candidates
+            .iter()
+            .map(|index| index.row_column())
+            .collect::<Vec<_>>();
+        let mut ordered = Vec::with_capacity(candidates.len());
+        let ordered_indices = self
+            .candidate_order
+            .order(&row_columns)
+            .into_iter()
+            .map(|(row, column)| Index::new(row, column));
+        for index in ordered_indices.chain(candidates.iter().copied()) {
+            if candidates.contains(&index) && !ordered.contains(&index) {
+                ordered.push(index);
+            }
+        }
+        ordered
+    }
+
+    // Of the board's solutions, the one whose chain covers the least total distance, counting each
+    // step as the number of cells moved along its row, column, or diagonal. This finds every
+    // solution (up to `MIN_DISTANCE_SOLUTION_LIMIT`) so it can be very slow on open boards.
+    pub fn solve_min_distance(board: Board) -> Result<Board, Error> {
+        Self::find_solutions(board, MIN_DISTANCE_SOLUTION_LIMIT)
+            .into_iter()
+            .min_by_key(total_distance)
+            .ok_or(Error::ImpossibleBoard)
+    }
+
+    pub fn count_solutions(board: Board, limit: usize) -> usize {
+        Self::find_solutions(board, limit).len()
+    }
+
+    // The search stops as soon as it finds a second solution
+    pub fn is_unique(board: Board) -> Result<bool, Error> {
+        let mut solver = Solver::new(board);
+        let mut solutions = Vec::new();
+        if solver.quick_feasibility() {
+            solver.find_internal(2, &mut solutions)?;
+        }
+        match solutions.len() {
+            0 => Err(Error::ImpossibleBoard),
+            1 => Ok(true),
+            _ => Ok(false),
+        }
+    }
+
+    pub fn find_solutions(board: Board, limit: usize) -> Vec<Board> {
+        let mut solver = Solver::new(board);
+        let mut solutions = Vec::new();
+        if solver.quick_feasibility() {
+            // Keep whatever was found before an error
+            let _ = solver.find_internal(limit, &mut solutions);
+        }
+        solutions
+    }
+
+    fn find_internal(&mut self, limit: usize, solutions: &mut Vec<Board>) -> Result<(), Error> {
+        self.frames.clear();
+        self.step_start = 1;
+        self.finished = None;
+        while solutions.len() < limit {
+            match self.try_step() {
+                Ok(StepResult::Solved) => {
+                    solutions.push(self.board.clone());
+                    // Carry on searching as if the last placement had failed
+                    self.finished = None;
+                    if self.take_back().is_none() {
+                        return Ok(());
+                    }
+                }
+                Ok(StepResult::Failed) => return Ok(()),
+                Ok(StepResult::Placed { .. }) | Ok(StepResult::Backtracked { .. }) => (),
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+
+    // The highest number goes in the final cell and nowhere else
+    fn fits_final(&self, index: Index, number: Number) -> bool {
+        let is_final = self.board[index.row_column()].pointer == Pointer::Final;
+        is_final == (number == self.max_number())
+    }
+
+    // A non-final cell whose arrow points straight off the board can never have a next cell
+    pub fn dead_pointers(&self) -> Vec<(usize, usize)> {
+        self.board
+            .enumerate_row_major()
+            .filter(|&((row, column), cell)| {
+                matches!(cell.pointer, Pointer::Go(_))
+                    && self.rays.get(Index::new(row, column)).is_empty()
+            })
+            .map(|(row_column, _)| row_column)
+            .collect()
+    }
+
+    // For each cell on the chain, in row-major order, how many empty cells lie along its arrow
+    pub fn candidate_counts(&self) -> Vec<usize> {
+        self.board
+            .enumerate_row_major()
+            .filter(|(_, cell)| !cell.is_blocked())
+            .map(|((row, column), _)| {
+                self.rays
+                    .get(Index::new(row, column))
+                    .iter()
+                    .filter(|index| self.board[index.row_column()].number.is_none())
+                    .count()
+            })
+            .collect()
+    }
+
+    // For each number, how many cells it could go in considering only the numbers already on the
+    // board. A profile of mostly ones means the puzzle is mostly forced.
+    pub fn branching_profile(&self) -> Vec<usize> {
+        (1..=self.max_number())
+            .map(|number| self.get_static_candidates(number).len())
+            .collect()
+    }
+
+    // The most consecutive numbers that each have exactly one place to go, filling in each forced
+    // number before looking at the next. Clues count as forced.
+    pub fn longest_forced_run(&self) -> usize {
+        let mut solver = Solver::with_rays(self.board.clone(), Arc::clone(&self.rays));
+        let mut longest = 0;
+        let mut run = 0;
+        for number in 1..=self.max_number() {
+            match solver.get_static_candidates(number).as_slice() {
+                &[index] => {
+                    solver.num_to_index.insert(number, index);
+                    solver.board[index.row_column()].number = Some(number);
+                    run += 1;
+                    longest = longest.max(run);
+                }
+                _ => run = 0,
             }
-            self.num_to_index.remove(&number);
-            self.board[row_column].number = None;
         }
+        longest
+    }
 
-        Err(Error::ImpossibleBoard)
+    fn get_static_candidates(&self, number: Number) -> Vec<Index> {
+        if let Some(&index) = self.num_to_index.get(&number) {
+            return vec![index];
+        }
+        let is_last = number == self.max_number();
+        let candidates = match self.get_possible_indices_from_prev(number - 1) {
+            Ok(candidates) => candidates,
+            Err(_) => return Vec::new(),
+        };
+        candidates
+            .into_iter()
+            .filter(|&index| {
+                let pointer = self.board[index.row_column()].pointer;
+                if is_last {
+                    return pointer == Pointer::Final;
+                }
+                let direction = match pointer {
+                    Pointer::Go(direction) => direction,
+                    Pointer::Final | Pointer::Blocked => return false,
+                };
+                match self.num_to_index.get(&(number + 1)) {
+                    Some(&next_index) => get_direction(index, next_index) == Some(direction),
+                    None => true,
+                }
+            })
+            .collect()
     }
 
     fn get_possible_indices_from_prev(&self, prev_number: Number) -> Result<Vec<Index>, Error> {
@@ -81,42 +1017,103 @@ impl Solver {
             None => return Ok(self.get_empty_indices()),
         };
 
-        let prev_pointer = self.board[prev_index.row_column()].pointer;
-        let direction = match prev_pointer {
-            Pointer::Go(direction) => direction,
-            Pointer::Final => {
-                return Err(Error::Internal(format!(
-                    "Previous index {:?} was final",
-                    prev_index
-                )))
-            }
-        };
+        if self.board[prev_index.row_column()].pointer == Pointer::Final {
+            return Err(Error::Internal(format!(
+                "Previous index {:?} was final",
+                prev_index
+            )));
+        }
 
-        Ok(self.get_empty_indices_in_direction(*prev_index, direction))
+        Ok(self
+            .rays
+            .get(*prev_index)
+            .iter()
+            .copied()
+            .filter(|index| self.board[index.row_column()].number.is_none())
+            .collect())
     }
 
     fn get_empty_indices_in_direction(&self, index: Index, direction: Direction) -> Vec<Index> {
-        let mut index = index;
-        let mut indices = Vec::new();
-        loop {
-            index = match index.step(direction) {
-                Some(index) => index,
-                None => return indices,
-            };
-            if index.row >= self.board.num_rows() || index.column >= self.board.num_columns() {
-                return indices;
-            }
-            if self.board[index.row_column()].number.is_none() {
-                indices.push(index);
+        self.get_indices_in_direction(index, direction)
+            .into_iter()
+            .filter(|index| {
+                let cell = self.board[index.row_column()];
+                cell.number.is_none() && !cell.is_blocked()
+            })
+            .collect()
+    }
+
+    fn get_indices_in_direction(&self, index: Index, direction: Direction) -> Vec<Index> {
+        get_indices_in_direction(
+            index,
+            direction,
+            self.board.num_rows(),
+            self.board.num_columns(),
+        )
+    }
+
+    // Treat the board as a directed graph where each cell has an edge to every cell along its
+    // arrow that could come next in the chain. A solution is a path through every cell, so every
+    // cell must be reachable from a possible start and must be able to reach the final cell. This
+    // is not exact, but it rejects many impossible boards without searching.
+    pub fn quick_feasibility(&self) -> bool {
+        let indices = self
+            .board
+            .indices_row_major()
+            .map(|(row, column)| Index::new(row, column))
+            .collect::<Vec<_>>();
+
+        let mut successors = HashMap::new();
+        let mut predecessors = HashMap::new();
+        for &index in indices.iter() {
+            for next in self.get_possible_successors(index) {
+                successors.entry(index).or_insert_with(Vec::new).push(next);
+                predecessors
+                    .entry(next)
+                    .or_insert_with(Vec::new)
+                    .push(index);
             }
         }
+
+        let starts = match self.num_to_index.get(&1) {
+            Some(&index) => vec![index],
+            None => self.get_empty_indices(),
+        };
+        let finals = indices
+            .iter()
+            .copied()
+            .filter(|index| self.board[index.row_column()].pointer == Pointer::Final)
+            .collect::<Vec<_>>();
+        if finals.is_empty() {
+            return false;
+        }
+
+        let num_elements = self.max_number();
+        reachable(starts, &successors).len() == num_elements
+            && reachable(finals, &predecessors).len() == num_elements
+    }
+
+    fn get_possible_successors(&self, index: Index) -> Vec<Index> {
+        let cell = self.board[index.row_column()];
+        self.rays
+            .get(index)
+            .iter()
+            .copied()
+            .filter(
+                |next| match (cell.number, self.board[next.row_column()].number) {
+                    (_, Some(1)) => false,
+                    (Some(number), Some(next_number)) => next_number == number + 1,
+                    _ => true,
+                },
+            )
+            .collect()
     }
 
     fn get_empty_indices(&self) -> Vec<Index> {
         self.board
             .enumerate_row_major()
             .filter_map(|((row, column), cell)| {
-                if cell.number.is_some() {
+                if cell.number.is_some() || cell.is_blocked() {
                     None
                 } else {
                     Some(Index::new(row, column))
@@ -126,7 +1123,7 @@ impl Solver {
     }
 
     fn max_number(&self) -> Number {
-        self.board.num_elements()
+        self.max_number
     }
 
     fn create_num_to_index(board: &Board) -> HashMap<Number, Index> {
@@ -141,6 +1138,138 @@ impl Solver {
     }
 }
 
+impl RayCache {
+    pub fn new(board: &Board) -> Self {
+        let num_rows = board.num_rows();
+        let num_columns = board.num_columns();
+        let rays = board
+            .enumerate_row_major()
+            .map(|((row, column), cell)| match cell.pointer {
+                // Blocked cells are never part of the chain, so arrows pass over them
+                Pointer::Go(direction) => get_indices_in_direction(
+                    Index::new(row, column),
+                    direction,
+                    num_rows,
+                    num_columns,
+                )
+                .into_iter()
+                .filter(|index| !board[index.row_column()].is_blocked())
+                .collect(),
+                Pointer::Final | Pointer::Blocked => Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        let pointers = board
+            .elements_row_major_iter()
+            .map(|cell| cell.pointer)
+            .collect::<Vec<_>>();
+        RayCache {
+            pointers: Array2D::from_row_major(&pointers, num_rows, num_columns)
+                .expect("Same dimensions as board"),
+            rays: Array2D::from_row_major(&rays, num_rows, num_columns)
+                .expect("Same dimensions as board"),
+        }
+    }
+
+    pub fn matches(&self, board: &Board) -> bool {
+        board.num_rows() == self.pointers.num_rows()
+            && board.num_columns() == self.pointers.num_columns()
+            && board
+                .elements_row_major_iter()
+                .zip(self.pointers.elements_row_major_iter())
+                .all(|(cell, &pointer)| cell.pointer == pointer)
+    }
+
+    fn get(&self, index: Index) -> &[Index] {
+        &self.rays[index.row_column()]
+    }
+}
+
+fn get_indices_in_direction(
+    index: Index,
+    direction: Direction,
+    num_rows: usize,
+    num_columns: usize,
+) -> Vec<Index> {
+    let mut index = index;
+    let mut indices = Vec::new();
+    loop {
+        index = match index.step(direction) {
+            Some(index) => index,
+            None => return indices,
+        };
+        if index.row >= num_rows || index.column >= num_columns {
+            return indices;
+        }
+        indices.push(index);
+    }
+}
+
+fn reachable(starts: Vec<Index>, edges: &HashMap<Index, Vec<Index>>) -> HashSet<Index> {
+    let mut seen = starts.iter().copied().collect::<HashSet<_>>();
+    let mut queue = starts.into_iter().collect::<VecDeque<_>>();
+    while let Some(index) = queue.pop_front() {
+        for &next in edges.get(&index).into_iter().flatten() {
+            if seen.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    seen
+}
+
+pub fn solutions_differ(a: &Board, b: &Board) -> Vec<(usize, usize)> {
+    let num_rows = a.num_rows().max(b.num_rows());
+    let num_columns = a.num_columns().max(b.num_columns());
+    let number_at = |board: &Board, row, column| board.get(row, column).map(|cell| cell.number);
+    (0..num_rows)
+        .flat_map(|row| (0..num_columns).map(move |column| (row, column)))
+        .filter(|&(row, column)| number_at(a, row, column) != number_at(b, row, column))
+        .collect()
+}
+
+// The sum of the distances between consecutive numbers on a solved board
+pub fn total_distance(board: &Board) -> usize {
+    let mut numbered = board
+        .enumerate_row_major()
+        .filter_map(|(row_column, cell)| Some((cell.number?, row_column)))
+        .collect::<Vec<_>>();
+    numbered.sort();
+    numbered
+        .windows(2)
+        .map(|window| {
+            let ((from_row, from_column), (to_row, to_column)) = (window[0].1, window[1].1);
+            abs_difference(from_row, to_row).max(abs_difference(from_column, to_column))
+        })
+        .sum()
+}
+
+// Attach each tree past the first `len` to the last branch of the one before it
+fn close_trace(trace: &mut Vec<TraceTree>, len: usize) {
+    while trace.len() > len {
+        let tree = trace.pop().expect("Longer than len");
+        let parent = trace.last_mut().expect("Longer than len");
+        if let Some(branch) = parent.branches.last_mut() {
+            branch.next = Some(Box::new(tree));
+        }
+    }
+}
+
+pub fn direction_between(from: (usize, usize), to: (usize, usize)) -> Option<Direction> {
+    get_direction(Index::new(from.0, from.1), Index::new(to.0, to.1))
+}
+
+pub fn step_from(
+    (row, column): (usize, usize),
+    direction: Direction,
+    distance: usize,
+) -> Option<(usize, usize)> {
+    let mut index = Index::new(row, column);
+    for _ in 0..distance {
+        index = index.step(direction)?;
+    }
+    Some(index.row_column())
+}
+
 fn get_direction(index1: Index, index2: Index) -> Option<Direction> {
     let Index {
         row: row1,
@@ -175,7 +1304,7 @@ fn get_direction(index1: Index, index2: Index) -> Option<Direction> {
     })
 }
 
-fn abs_difference<T: std::ops::Sub<Output = T> + Ord>(x: T, y: T) -> T {
+pub fn abs_difference<T: std::ops::Sub<Output = T> + Ord>(x: T, y: T) -> T {
     if x < y {
         y - x
     } else {
@@ -188,6 +1317,20 @@ impl Index {
         Self { row, column }
     }
 
+    // For coordinates that come from outside the solver and might not be on the board
+    pub fn new_checked(
+        row: usize,
+        column: usize,
+        num_rows: usize,
+        num_columns: usize,
+    ) -> Option<Self> {
+        if row < num_rows && column < num_columns {
+            Some(Self::new(row, column))
+        } else {
+            None
+        }
+    }
+
     pub fn row_column(self) -> (usize, usize) {
         (self.row, self.column)
     }
@@ -260,6 +1403,9 @@ mod test {
         ("*") => {
             Pointer::Final;
         };
+        ("#") => {
+            Pointer::Blocked;
+        };
     }
 
     #[test]
@@ -376,6 +1522,15 @@ mod test {
         assert_eq!(edge_west.step(Northwest), None);
     }
 
+    #[test]
+    fn test_index_new_checked() {
+        assert_eq!(Index::new_checked(0, 0, 3, 4), Some(Index::new(0, 0)));
+        assert_eq!(Index::new_checked(2, 3, 3, 4), Some(Index::new(2, 3)));
+        assert_eq!(Index::new_checked(3, 0, 3, 4), None);
+        assert_eq!(Index::new_checked(0, 4, 3, 4), None);
+        assert_eq!(Index::new_checked(0, 0, 0, 0), None);
+    }
+
     #[test]
     fn test_empty_indices() {
         let board = Array2D::from_rows(&vec![
@@ -423,13 +1578,170 @@ mod test {
         let expected = vec![];
         assert_eq!(actual, expected);
 
-        let actual = solver.get_empty_indices_in_direction(Index::new(0, 2), Southwest);
-        let expected = indices(vec![(2, 0)]);
-        assert_eq!(actual, expected);
+        let actual = solver.get_empty_indices_in_direction(Index::new(0, 2), Southwest);
+        let expected = indices(vec![(2, 0)]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_get_possible_indices_from_prev() -> Result<(), super::Error> {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("s")],
+            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e"), cell!("w"), cell!("*", 9)],
+        ])
+        .unwrap();
+        let solver = Solver::new(board);
+
+        let indices = |tuples: Vec<(usize, usize)>| {
+            tuples
+                .into_iter()
+                .map(|(row, column)| Index::new(row, column))
+                .collect::<Vec<_>>()
+        };
+
+        let actual = solver.get_possible_indices_from_prev(1)?;
+        let expected = indices(vec![(0, 1), (0, 2)]);
+        assert_eq!(actual, expected);
+
+        let actual = solver.get_possible_indices_from_prev(2)?;
+        let expected = indices(vec![(0, 1), (0, 2), (1, 0), (2, 0), (2, 1)]);
+        assert_eq!(actual, expected);
+
+        let actual = solver.get_possible_indices_from_prev(5)?;
+        let expected = indices(vec![(1, 0)]);
+        assert_eq!(actual, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_quick_feasibility() {
+        use crate::game::Game;
+        let solver = Solver::new(Game::example().into_board());
+        assert!(solver.quick_feasibility());
+
+        // Nothing points at the bottom-left cell
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("s")],
+            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e"), cell!("e"), cell!("*", 9)],
+        ])
+        .unwrap();
+        let solver = Solver::new(board);
+        assert!(!solver.quick_feasibility());
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        use crate::game::Game;
+        assert_eq!(Solver::count_solutions(Game::example().into_board(), 2), 1);
+
+        let ambiguous = Array2D::from_rows(&vec![
+            vec![cell!("se"), cell!("e"), cell!("*", 9)],
+            vec![cell!("n"), cell!("n"), cell!("w")],
+            vec![cell!("e"), cell!("ne"), cell!("w")],
+        ])
+        .unwrap();
+        assert_eq!(Solver::count_solutions(ambiguous.clone(), 10), 3);
+        assert_eq!(Solver::count_solutions(ambiguous, 2), 2);
+
+        let initial_board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("e")],
+            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e"), cell!("w"), cell!("*", 9)],
+        ])
+        .unwrap();
+        assert_eq!(Solver::count_solutions(initial_board, 2), 0);
+    }
+
+    #[test]
+    fn test_solutions_differ() {
+        let ambiguous = Array2D::from_rows(&vec![
+            vec![cell!("se"), cell!("e"), cell!("*", 9)],
+            vec![cell!("n"), cell!("n"), cell!("w")],
+            vec![cell!("e"), cell!("ne"), cell!("w")],
+        ])
+        .unwrap();
+        let solutions = Solver::find_solutions(ambiguous, 2);
+        assert_eq!(solutions.len(), 2);
+
+        let differences = solutions_differ(&solutions[0], &solutions[1]);
+        assert!(!differences.is_empty());
+        for &(row, column) in differences.iter() {
+            assert_ne!(solutions[0][(row, column)], solutions[1][(row, column)]);
+        }
+        assert_eq!(solutions_differ(&solutions[0], &solutions[0]), vec![]);
+    }
+
+    #[test]
+    fn test_solve_with_rays() {
+        let layout = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("s")],
+            vec![cell!("se"), cell!("w", 5), cell!("w")],
+            vec![cell!("e"), cell!("w"), cell!("*", 9)],
+        ])
+        .unwrap();
+        let rays = Arc::new(RayCache::new(&layout));
+
+        let other_clues = Array2D::from_rows(&vec![
+            vec![cell!("e"), cell!("e"), cell!("s", 3)],
+            vec![cell!("se"), cell!("w"), cell!("w", 4)],
+            vec![cell!("e", 8), cell!("w"), cell!("*", 9)],
+        ])
+        .unwrap();
+        let expected = Ok(Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e", 2), cell!("s", 3)],
+            vec![cell!("se", 6), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e", 8), cell!("w", 7), cell!("*", 9)],
+        ])
+        .unwrap());
+
+        assert_eq!(Solver::solve_with_rays(layout, rays.clone()), expected);
+        assert_eq!(Solver::solve_with_rays(other_clues, rays.clone()), expected);
+
+        let different_layout = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("e")],
+            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e"), cell!("w"), cell!("*", 9)],
+        ])
+        .unwrap();
+        assert_eq!(
+            Solver::solve_with_rays(different_layout, rays),
+            Err(super::Error::LayoutMismatch)
+        );
+    }
+
+    #[test]
+    fn test_dead_pointers() {
+        use crate::game::Game;
+        assert_eq!(
+            Solver::new(Game::example().into_board()).dead_pointers(),
+            vec![]
+        );
+
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("n"), cell!("s")],
+            vec![cell!("se"), cell!("w", 5), cell!("e", 4)],
+            vec![cell!("e"), cell!("w"), cell!("*", 9)],
+        ])
+        .unwrap();
+        let solver = Solver::new(board.clone());
+        assert_eq!(solver.dead_pointers(), vec![(0, 1), (1, 2)]);
+        assert_eq!(Solver::solve(board), Err(super::Error::ImpossibleBoard));
     }
 
     #[test]
-    fn test_get_possible_indices_from_prev() -> Result<(), super::Error> {
+    fn test_branching_profile() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e", 2), cell!("s")],
+            vec![cell!("se", 6), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e", 8), cell!("w"), cell!("*", 9)],
+        ])
+        .unwrap();
+        let solver = Solver::new(board);
+        assert_eq!(solver.branching_profile(), vec![1, 1, 1, 1, 1, 1, 1, 1, 1]);
+
         let board = Array2D::from_rows(&vec![
             vec![cell!("e", 1), cell!("e"), cell!("s")],
             vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
@@ -437,27 +1749,27 @@ mod test {
         ])
         .unwrap();
         let solver = Solver::new(board);
+        assert_eq!(solver.branching_profile(), vec![1, 2, 1, 1, 1, 1, 5, 2, 1]);
+    }
 
-        let indices = |tuples: Vec<(usize, usize)>| {
-            tuples
-                .into_iter()
-                .map(|(row, column)| Index::new(row, column))
-                .collect::<Vec<_>>()
-        };
-
-        let actual = solver.get_possible_indices_from_prev(1)?;
-        let expected = indices(vec![(0, 1), (0, 2)]);
-        assert_eq!(actual, expected);
-
-        let actual = solver.get_possible_indices_from_prev(2)?;
-        let expected = indices(vec![(0, 1), (0, 2), (1, 0), (2, 0), (2, 1)]);
-        assert_eq!(actual, expected);
-
-        let actual = solver.get_possible_indices_from_prev(5)?;
-        let expected = indices(vec![(1, 0)]);
-        assert_eq!(actual, expected);
+    #[test]
+    fn test_longest_forced_run() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e", 2), cell!("s")],
+            vec![cell!("se", 6), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e", 8), cell!("w"), cell!("*", 9)],
+        ])
+        .unwrap();
+        assert_eq!(Solver::new(board).longest_forced_run(), 9);
 
-        Ok(())
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("s"), cell!("w"), cell!("sw")],
+            vec![cell!("se"), cell!("se"), cell!("s"), cell!("sw")],
+            vec![cell!("ne"), cell!("e"), cell!("w"), cell!("n")],
+            vec![cell!("n"), cell!("w"), cell!("n"), cell!("*", 16)],
+        ])
+        .unwrap();
+        assert_eq!(Solver::new(board).longest_forced_run(), 2);
     }
 
     #[test]
@@ -480,6 +1792,136 @@ mod test {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_solve_until() {
+        use crate::game::Game;
+        use std::time::Duration;
+
+        let far_future = Instant::now() + Duration::from_secs(60 * 60);
+        let expected = Solver::solve(Game::example().into_board());
+        assert!(expected.is_ok());
+        assert_eq!(
+            Solver::solve_until(Game::example().into_board(), far_future),
+            expected
+        );
+
+        let past = Instant::now();
+        assert_eq!(
+            Solver::solve_until(Game::example().into_board(), past),
+            Err(super::Error::Timeout)
+        );
+    }
+
+    #[test]
+    fn test_search_stats() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("s")],
+            vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
+            vec![cell!("e"), cell!("w"), cell!("*", 9)],
+        ])
+        .unwrap();
+        let stats = Solver::search_stats(board.clone(), 1000);
+        assert!(stats.solved);
+        assert!(stats.placements >= 5);
+        assert_eq!(stats.placements - stats.backtracks, 5);
+
+        let stats = Solver::search_stats(board, 1);
+        assert!(!stats.solved);
+        assert_eq!(stats.placements, 1);
+    }
+
+    #[test]
+    fn test_solve_traced() {
+        fn solution_path(tree: &TraceTree) -> Vec<(Number, (usize, usize))> {
+            let mut path = Vec::new();
+            let mut tree = Some(tree);
+            while let Some(current) = tree {
+                let branch = current
+                    .branches
+                    .iter()
+                    .find(|branch| branch.succeeded)
+                    .expect("Each number on the way has a successful branch");
+                path.push((current.number, branch.at));
+                tree = branch.next.as_deref();
+            }
+            path
+        }
+
+        // Without a clue for 1 the search decides it too
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e"), cell!("sw")],
+            vec![cell!("e"), cell!("*", 4)],
+        ])
+        .unwrap();
+        let (result, tree) = Solver::solve_traced(board.clone());
+        assert_eq!(result, Solver::solve(board));
+        assert_eq!(tree.number, 1);
+        assert!(tree.branches.iter().all(|branch| !branch.clue));
+        assert_eq!(
+            solution_path(&tree),
+            vec![(1, (0, 0)), (2, (0, 1)), (3, (1, 0)), (4, (1, 1))]
+        );
+
+        let (result, tree) = Solver::solve_traced(Game::example().into_board());
+        let solution = result.unwrap();
+        assert_eq!(
+            Ok(solution.clone()),
+            Solver::solve(Game::example().into_board())
+        );
+        assert_eq!(tree.number, 1);
+        assert_eq!(tree.branches.len(), 1);
+        assert!(tree.branches[0].clue);
+        let path = solution_path(&tree);
+        assert_eq!(path.len(), 16);
+        for (number, at) in path {
+            assert_eq!(solution[at].number, Some(number));
+        }
+
+        // Every branch fails on an impossible board
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("sw"), cell!("w")],
+            vec![cell!("e"), cell!("n"), cell!("*", 6)],
+        ])
+        .unwrap();
+        let (result, tree) = Solver::solve_traced(board.clone());
+        assert_eq!(result, Solver::solve(board));
+        assert!(result.is_err());
+        assert_eq!(tree.number, 1);
+        assert!(tree.branches[0].next.as_ref().unwrap().branches.len() > 1);
+        fn any_succeeded(tree: &TraceTree) -> bool {
+            tree.branches.iter().any(|branch| {
+                branch.succeeded || branch.next.as_deref().into_iter().any(any_succeeded)
+            })
+        }
+        assert!(!any_succeeded(&tree));
+    }
+
+    #[test]
+    fn test_solve_text() {
+        let game = solve_text("e1,e,s,w3\ns,s12,w5,w\nse,w,e,n\ne,e,n,*16").unwrap();
+        assert!(game.is_solved());
+        assert_eq!(
+            Some(game.into_board()),
+            Solver::solve(Game::example().into_board()).ok()
+        );
+
+        assert!(matches!(
+            solve_text("e1,x"),
+            Err(SolveError::Parse(parse::ParseError::Syntax { .. }))
+        ));
+        assert_eq!(
+            solve_text("e1,e\ns,*3"),
+            Err(SolveError::Game(game::Error::WrongFinalNumber {
+                actual: 3,
+                expected: 4,
+            }))
+        );
+        assert_eq!(
+            solve_text("e1,e,e\nse,w5,w4\ne,w,*9"),
+            Err(SolveError::Solver(super::Error::ImpossibleBoard))
+        );
+    }
+
     #[test]
     fn test_impossible_board() -> Result<(), super::Error> {
         let initial_board = Array2D::from_rows(&vec![
@@ -508,7 +1950,7 @@ mod test {
     #[test]
     fn test_example_board() {
         use crate::game::Game;
-        let initial_board = Game::example().board;
+        let initial_board = Game::example().into_board();
 
         let actual = Solver::solve(initial_board);
         let expected = Ok(Array2D::from_rows(&vec![
@@ -557,4 +1999,586 @@ mod test {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_blocked_cells() {
+        // Arrows pass over the blocked center cell
+        let initial_board = Array2D::from_rows(&vec![
+            vec![cell!("se", 1), cell!("e"), cell!("s")],
+            vec![cell!("s"), cell!("#"), cell!("w")],
+            vec![cell!("*", 8), cell!("n"), cell!("w")],
+        ])
+        .unwrap();
+
+        let actual = Solver::solve(initial_board);
+        let expected = Ok(Array2D::from_rows(&vec![
+            vec![cell!("se", 1), cell!("e", 4), cell!("s", 5)],
+            vec![cell!("s", 7), cell!("#"), cell!("w", 6)],
+            vec![cell!("*", 8), cell!("n", 3), cell!("w", 2)],
+        ])
+        .unwrap());
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_solve_from() {
+        let initial_board = Array2D::from_rows(&vec![
+            vec![cell!("e"), cell!("s"), cell!("w", 5), cell!("sw")],
+            vec![cell!("se"), cell!("se"), cell!("s"), cell!("sw")],
+            vec![cell!("ne"), cell!("e"), cell!("w"), cell!("n")],
+            vec![cell!("n"), cell!("w"), cell!("n"), cell!("*", 16)],
+        ])
+        .unwrap();
+
+        let natural = Solver::solve(initial_board.clone());
+        assert_eq!(
+            natural.as_ref().map(|board| board[(0, 0)].number),
+            Ok(Some(1))
+        );
+        assert_eq!(Solver::solve_from(initial_board.clone(), (0, 0)), natural);
+
+        assert_eq!(
+            Solver::solve_from(initial_board.clone(), (0, 2)),
+            Err(Error::InvalidStart(0, 2))
+        );
+        assert_eq!(
+            Solver::solve_from(initial_board.clone(), (4, 0)),
+            Err(Error::InvalidStart(4, 0))
+        );
+        assert_eq!(
+            Error::InvalidStart(0, 2).to_string(),
+            "row 1, column 3 can't be the start"
+        );
+        assert_eq!(
+            Solver::solve_from(initial_board, (1, 1)),
+            Err(Error::ImpossibleBoard)
+        );
+    }
+
+    #[test]
+    fn test_solve_frames() {
+        let initial_board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("s"), cell!("w", 5), cell!("sw")],
+            vec![cell!("se"), cell!("se"), cell!("s"), cell!("sw")],
+            vec![cell!("ne"), cell!("e"), cell!("w"), cell!("n")],
+            vec![cell!("n"), cell!("w"), cell!("n"), cell!("*", 16)],
+        ])
+        .unwrap();
+
+        let frames = Solver::solve_frames(initial_board.clone()).unwrap();
+        assert_eq!(frames.len(), 13);
+        assert_eq!(frames[0][(0, 3)].number, Some(2));
+        assert_eq!(frames[0][(1, 2)].number, None);
+        assert_eq!(frames[1][(1, 2)].number, Some(3));
+        assert_eq!(frames.last(), Solver::solve(initial_board).ok().as_ref());
+    }
+
+    #[test]
+    fn test_resolve_after_edit() {
+        let initial_board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("s"), cell!("w", 5), cell!("sw")],
+            vec![cell!("se"), cell!("se"), cell!("s"), cell!("sw")],
+            vec![cell!("ne"), cell!("e"), cell!("w"), cell!("n")],
+            vec![cell!("n"), cell!("w"), cell!("n"), cell!("*", 16)],
+        ])
+        .unwrap();
+        let expected = Solver::solve(initial_board.clone()).unwrap();
+
+        let mut solver = Solver::new(initial_board);
+        solver.solve_in_place().unwrap();
+        assert_eq!(solver.board(), &expected);
+
+        // Clearing a number the solver placed gets it placed again
+        solver.board_mut()[(2, 0)].number = None;
+        assert_eq!(solver.resolve_after_edit((2, 0)), Ok(()));
+        assert_eq!(solver.board(), &expected);
+
+        // A wrong number is searched around, and fixing it gives back the original solution
+        solver.board_mut()[(1, 1)].number = Some(9);
+        assert_eq!(
+            solver.resolve_after_edit((1, 1)),
+            Err(Error::ImpossibleBoard)
+        );
+        solver.board_mut()[(1, 1)].number = None;
+        assert_eq!(solver.resolve_after_edit((1, 1)), Ok(()));
+        assert_eq!(solver.board(), &expected);
+
+        solver.board_mut()[(1, 1)].number = Some(0);
+        assert_eq!(
+            solver.resolve_after_edit((1, 1)),
+            Err(Error::Game(game::Error::NoZeroAllowed))
+        );
+        assert_eq!(
+            solver.resolve_after_edit((4, 0)),
+            Err(Error::Game(game::Error::OutOfBounds(4, 0)))
+        );
+    }
+
+    #[test]
+    fn test_solve_batch() {
+        let second = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("s"), cell!("w", 5), cell!("sw")],
+            vec![cell!("se"), cell!("se"), cell!("s"), cell!("sw")],
+            vec![cell!("ne"), cell!("e"), cell!("w"), cell!("n")],
+            vec![cell!("n"), cell!("w"), cell!("n"), cell!("*", 16)],
+        ])
+        .unwrap();
+        let boards = vec![Game::example().into_board(), second];
+        let expected = boards
+            .iter()
+            .cloned()
+            .map(Solver::solve)
+            .collect::<Vec<_>>();
+        assert!(expected.iter().all(Result::is_ok));
+        assert_eq!(Solver::solve_batch(boards), expected);
+    }
+
+    #[test]
+    fn test_solve_canonical() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("se"), cell!("e"), cell!("*", 9)],
+            vec![cell!("n"), cell!("n"), cell!("w")],
+            vec![cell!("e"), cell!("ne"), cell!("w")],
+        ])
+        .unwrap();
+
+        let smallest = Solver::find_solutions(board.clone(), 10)
+            .into_iter()
+            .min_by_key(|solution| Game::new(solution.clone()).unwrap().solution_path())
+            .unwrap();
+        let canonical = Solver::solve_canonical(board.clone());
+        assert_eq!(canonical, Ok(smallest));
+        assert_eq!(Solver::solve_canonical(board), canonical);
+    }
+
+    #[test]
+    fn test_solve_partial() {
+        // 2 must be in the top right, but then 3 would have to be the final cell
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("s")],
+            vec![cell!("n"), cell!("*", 4)],
+        ])
+        .unwrap();
+        assert_eq!(Solver::solve(board.clone()), Err(Error::ImpossibleBoard));
+
+        let (partial, remaining) = Solver::solve_partial(board);
+        let expected = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("s", 2)],
+            vec![cell!("n"), cell!("*", 4)],
+        ])
+        .unwrap();
+        assert_eq!(partial, expected);
+        assert_eq!(remaining, vec![(1, 0)]);
+
+        let (solved, remaining) = Solver::solve_partial(Game::example().into_board());
+        assert_eq!(Solver::solve(Game::example().into_board()), Ok(solved));
+        assert!(remaining.is_empty());
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_trace_logging() {
+        use std::sync::Mutex;
+
+        struct Capture(Mutex<Vec<String>>);
+
+        impl log::Log for Capture {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record) {
+                self.0.lock().unwrap().push(record.args().to_string());
+            }
+
+            fn flush(&self) {}
+        }
+
+        let logger: &'static Capture = Box::leak(Box::new(Capture(Mutex::new(Vec::new()))));
+        log::set_logger(logger).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        Solver::solve(Game::example().into_board()).unwrap();
+        let records = logger.0.lock().unwrap();
+        assert!(records.iter().any(|record| record.starts_with("Placed ")));
+        assert!(records
+            .iter()
+            .any(|record| record.starts_with("Search finished")));
+    }
+
+    #[test]
+    fn test_is_unique() {
+        assert_eq!(Solver::is_unique(Game::example().into_board()), Ok(true));
+
+        let ambiguous = Array2D::from_rows(&vec![
+            vec![cell!("se"), cell!("e"), cell!("*", 9)],
+            vec![cell!("n"), cell!("n"), cell!("w")],
+            vec![cell!("e"), cell!("ne"), cell!("w")],
+        ])
+        .unwrap();
+        assert_eq!(Solver::is_unique(ambiguous), Ok(false));
+
+        let impossible = Array2D::from_rows(&vec![vec![cell!("w", 1), cell!("*", 2)]]).unwrap();
+        assert_eq!(Solver::is_unique(impossible), Err(Error::ImpossibleBoard));
+    }
+
+    #[test]
+    fn test_solve_with_order() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("se"), cell!("e"), cell!("*", 9)],
+            vec![cell!("n"), cell!("n"), cell!("w")],
+            vec![cell!("e"), cell!("ne"), cell!("w")],
+        ])
+        .unwrap();
+
+        let nearest = Solver::solve_with_order(board.clone(), CandidateOrder::PreferNearest);
+        let farthest = Solver::solve_with_order(board.clone(), CandidateOrder::PreferFarthest);
+        assert_eq!(nearest, Solver::solve(board.clone()));
+        assert!(nearest.is_ok());
+        assert!(farthest.is_ok());
+        assert_ne!(nearest, farthest);
+
+        let reversed =
+            Solver::solve_with_order(board.clone(), |candidates: &[(usize, usize)]| {
+                candidates.iter().rev().copied().collect::<Vec<_>>()
+            });
+        assert_eq!(reversed, farthest);
+        let game = Game::new(board.clone()).unwrap();
+        assert_eq!(game.verify_solution(&reversed.unwrap()), Ok(()));
+
+        // Cells that weren't candidates are ignored and left out ones are tried last, so the order
+        // can't stop the search from finding a solution
+        let bogus = Solver::solve_with_order(board.clone(), |_: &[(usize, usize)]| vec![(0, 0)]);
+        assert_eq!(bogus, nearest);
+        let truncated =
+            Solver::solve_with_order(board.clone(), |candidates: &[(usize, usize)]| {
+                candidates.iter().rev().take(1).copied().collect::<Vec<_>>()
+            });
+        assert_eq!(game.verify_solution(&truncated.unwrap()), Ok(()));
+        let repeated = Solver::solve_with_order(board, |candidates: &[(usize, usize)]| {
+            candidates
+                .iter()
+                .chain(candidates)
+                .copied()
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(repeated, nearest);
+    }
+
+    #[test]
+    fn test_step_search() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("s"), cell!("w", 5), cell!("sw")],
+            vec![cell!("se"), cell!("se"), cell!("s"), cell!("sw")],
+            vec![cell!("ne"), cell!("e"), cell!("w"), cell!("n")],
+            vec![cell!("n"), cell!("w"), cell!("n"), cell!("*", 16)],
+        ])
+        .unwrap();
+        let mut solver = Solver::new(board.clone());
+        let mut num_backtracks = 0;
+        loop {
+            match solver.step().unwrap() {
+                StepResult::Placed { number, at } => {
+                    assert_eq!(solver.board()[at].number, Some(number));
+                }
+                StepResult::Backtracked { number } => {
+                    assert!(!solver
+                        .board()
+                        .elements_row_major_iter()
+                        .any(|cell| cell.number == Some(number)));
+                    num_backtracks += 1;
+                }
+                StepResult::Solved => break,
+                StepResult::Failed => panic!("Board should be solvable"),
+            }
+        }
+        assert!(num_backtracks > 0);
+        assert_eq!(Ok(solver.board().clone()), Solver::solve(board.clone()));
+        assert_eq!(solver.step(), Ok(StepResult::Solved));
+
+        let impossible = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("s"), cell!("w")],
+            vec![cell!("n"), cell!("n"), cell!("*", 6)],
+        ])
+        .unwrap();
+        let mut solver = Solver::new(impossible.clone());
+        let result = (0..100)
+            .map(|_| solver.step().unwrap())
+            .find(|result| *result == StepResult::Failed || *result == StepResult::Solved);
+        assert_eq!(result, Some(StepResult::Failed));
+        assert_eq!(solver.board(), &impossible);
+
+        // Errors come back from the step that hit them rather than turning into `Failed`
+        let final_before_last =
+            Array2D::from_rows(&vec![vec![cell!("e", 1), cell!("*", 2), cell!("w")]]).unwrap();
+        let mut solver = Solver::new(final_before_last);
+        assert!(matches!(solver.step(), Err(Error::Internal(_))));
+
+        let mut solver = Solver::new(board.clone());
+        solver.placement_limit = Some(1);
+        assert!(matches!(solver.step(), Ok(StepResult::Placed { .. })));
+        assert_eq!(solver.step(), Err(Error::PlacementLimit));
+        assert_eq!(solver.step(), Err(Error::PlacementLimit));
+
+        // Raising the limit each time it is hit carries on the same search
+        let result = loop {
+            match solver.step() {
+                Ok(StepResult::Placed { .. }) | Ok(StepResult::Backtracked { .. }) => (),
+                Ok(result) => break result,
+                Err(Error::PlacementLimit) => {
+                    solver.placement_limit = solver.placement_limit.map(|limit| limit + 1);
+                }
+                Err(error) => panic!("Unexpected error: {:?}", error),
+            }
+        };
+        assert_eq!(result, StepResult::Solved);
+        assert_eq!(Ok(solver.board().clone()), Solver::solve(board.clone()));
+
+        let misnumbered = Array2D::from_rows(&vec![
+            vec![cell!("e", 1), cell!("e"), cell!("*", 3)],
+            vec![cell!("n"), cell!("w"), cell!("#")],
+        ])
+        .unwrap();
+        let mut solver = Solver::new(misnumbered).with_strict_validation(true);
+        assert!(matches!(solver.step(), Err(Error::PreCheck(_))));
+    }
+
+    #[test]
+    fn test_solve_large_board() {
+        // Snake back and forth across a 15x15 board with only every tenth number given, so the
+        // search has to fill in a 225-cell chain
+        let size = 15;
+        let path = (0..size)
+            .flat_map(|row| {
+                (0..size).map(move |column| {
+                    if row % 2 == 0 {
+                        (row, column)
+                    } else {
+                        (row, size - 1 - column)
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let solution = crate::generate::Generator::generate_path(&path, size, size).unwrap();
+        let mut clues = solution.clone();
+        for (i, &row_column) in path.iter().enumerate() {
+            if i % 10 != 0 && i != path.len() - 1 {
+                clues[row_column].number = None;
+            }
+        }
+        let game = Game::new(clues.clone()).unwrap();
+        let solved = Solver::solve(clues.clone()).unwrap();
+        assert_eq!(game.verify_solution(&solved), Ok(()));
+        assert_eq!(
+            Solver::find_solutions(clues.clone(), 1),
+            vec![solved.clone()]
+        );
+        assert_eq!(find_recursive(clues, 1), vec![solved]);
+    }
+
+    // The search as it was before it ran one step at a time, kept to check the stepping search
+    // against
+    fn find_recursive(board: Board, limit: usize) -> Vec<Board> {
+        fn search(solver: &mut Solver, number: Number, limit: usize, solutions: &mut Vec<Board>) {
+            if solutions.len() >= limit {
+                return;
+            }
+            if number > solver.max_number() {
+                solutions.push(solver.board.clone());
+                return;
+            }
+            if solver.num_to_index.contains_key(&number) {
+                return search(solver, number + 1, limit, solutions);
+            }
+            let possible_indices = match solver.get_possible_indices_from_prev(number - 1) {
+                Ok(possible_indices) => possible_indices,
+                Err(_) => return,
+            };
+            for index in possible_indices {
+                if !solver.fits_next(index, number) {
+                    continue;
+                }
+                solver.num_to_index.insert(number, index);
+                solver.board[index.row_column()].number = Some(number);
+                search(solver, number + 1, limit, solutions);
+                solver.num_to_index.remove(&number);
+                solver.board[index.row_column()].number = None;
+                if solutions.len() >= limit {
+                    return;
+                }
+            }
+        }
+
+        let mut solver = Solver::new(board);
+        let mut solutions = Vec::new();
+        if solver.quick_feasibility() {
+            search(&mut solver, 1, limit, &mut solutions);
+        }
+        solutions
+    }
+
+    #[test]
+    fn test_search_matches_recursive() {
+        let boards = vec![
+            Game::example().into_board(),
+            Array2D::from_rows(&vec![
+                vec![cell!("e", 1), cell!("e"), cell!("s")],
+                vec![cell!("se"), cell!("w", 5), cell!("w", 4)],
+                vec![cell!("e"), cell!("w"), cell!("*", 9)],
+            ])
+            .unwrap(),
+            Array2D::from_rows(&vec![
+                vec![cell!("se"), cell!("e"), cell!("*", 9)],
+                vec![cell!("n"), cell!("n"), cell!("w")],
+                vec![cell!("e"), cell!("ne"), cell!("w")],
+            ])
+            .unwrap(),
+        ];
+        for board in boards {
+            let expected = find_recursive(board.clone(), 10);
+            assert_eq!(Solver::find_solutions(board.clone(), 10), expected);
+            assert_eq!(Solver::solve(board).ok(), expected.first().cloned());
+        }
+    }
+
+    #[test]
+    fn test_solve_final_before_last() {
+        // The final cell holds 2 but the chain is 3 long, so deciding 3 finds the final cell before it
+        let board =
+            Array2D::from_rows(&vec![vec![cell!("e", 1), cell!("*", 2), cell!("w")]]).unwrap();
+        assert!(Solver::new(board.clone()).quick_feasibility());
+        assert_eq!(
+            Solver::new(board.clone()).get_possible_indices_from_prev(2),
+            Err(Error::Internal(
+                "Previous index Index { row: 0, column: 1 } was final".to_string()
+            ))
+        );
+        assert!(matches!(
+            Solver::solve(board.clone()),
+            Err(Error::Internal(_))
+        ));
+        assert!(matches!(
+            Solver::is_unique(board.clone()),
+            Err(Error::Internal(_))
+        ));
+        assert_eq!(
+            Solver::find_solutions(board.clone(), 2),
+            Vec::<Board>::new()
+        );
+        assert!(matches!(
+            Solver::solve_traced(board).0,
+            Err(Error::Internal(_))
+        ));
+    }
+
+    #[test]
+    fn test_solve_seeded() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("se"), cell!("e"), cell!("*", 9)],
+            vec![cell!("n"), cell!("n"), cell!("w")],
+            vec![cell!("e"), cell!("ne"), cell!("w")],
+        ])
+        .unwrap();
+        let game = Game::new(board.clone()).unwrap();
+        assert_eq!(
+            Solver::solve_seeded(board.clone(), 5),
+            Solver::solve_seeded(board.clone(), 5)
+        );
+
+        let solutions = (0..20)
+            .map(|seed| Solver::solve_seeded(board.clone(), seed).unwrap())
+            .collect::<Vec<_>>();
+        assert!(solutions.iter().any(|solution| solution != &solutions[0]));
+        assert!(solutions
+            .iter()
+            .all(|solution| game.verify_solution(solution).is_ok()));
+    }
+
+    #[test]
+    fn test_solve_min_distance() {
+        let board = Array2D::from_rows(&vec![
+            vec![cell!("se"), cell!("*", 9), cell!("sw")],
+            vec![cell!("e"), cell!("nw"), cell!("nw")],
+            vec![cell!("e"), cell!("nw"), cell!("n")],
+        ])
+        .unwrap();
+        let solutions = Solver::find_solutions(board.clone(), 100);
+        assert!(solutions.len() > 1);
+        let distances = solutions.iter().map(total_distance).collect::<Vec<_>>();
+        let min_distance = *distances.iter().min().unwrap();
+        assert!(distances.iter().any(|&distance| distance > min_distance));
+
+        let solution = Solver::solve_min_distance(board).unwrap();
+        assert!(solutions.contains(&solution));
+        assert_eq!(total_distance(&solution), min_distance);
+
+        let impossible = Array2D::from_rows(&vec![vec![cell!("w", 1), cell!("*", 2)]]).unwrap();
+        assert_eq!(
+            Solver::solve_min_distance(impossible),
+            Err(Error::ImpossibleBoard)
+        );
+    }
+
+    #[test]
+    fn test_single_cell() {
+        let numbered = Array2D::from_rows(&vec![vec![cell!("*", 1)]]).unwrap();
+        assert_eq!(Solver::solve(numbered.clone()), Ok(numbered.clone()));
+
+        let unnumbered = Array2D::from_rows(&vec![vec![cell!("*")]]).unwrap();
+        assert_eq!(Solver::solve(unnumbered), Ok(numbered));
+    }
+
+    #[test]
+    fn test_unnumbered_final() {
+        let mut board = Game::example().into_board();
+        board[(3, 3)] = cell!("*");
+        let expected = Solver::solve(Game::example().into_board());
+        assert_eq!(Solver::solve(board.clone()), expected);
+        assert_eq!(Solver::count_solutions(board, 2), 1);
+    }
+
+    #[test]
+    fn test_strict_validation() {
+        let strict_solve = |board: Board| {
+            let mut solver = Solver::new(board).with_strict_validation(true);
+            solver.solve_in_place().map(|()| solver.board)
+        };
+
+        let dead = Array2D::from_rows(&vec![vec![cell!("w", 1), cell!("*", 2)]]).unwrap();
+        assert_eq!(Solver::solve(dead.clone()), Err(Error::ImpossibleBoard));
+        assert_eq!(
+            strict_solve(dead),
+            Err(Error::PreCheck(PreCheck::DeadPointer(0, 0)))
+        );
+        assert_eq!(
+            PreCheck::DeadPointer(0, 0).to_string(),
+            "the arrow at row 1, column 1 points off the board"
+        );
+
+        let mut mismatch = Game::example().into_board();
+        mismatch[(0, 1)].number = Some(2);
+        mismatch[(2, 2)].number = Some(4);
+        assert_eq!(
+            strict_solve(mismatch),
+            Err(Error::PreCheck(PreCheck::AnchorMismatch(3)))
+        );
+
+        let mut wrong_final = Game::example().into_board();
+        wrong_final[(3, 3)].number = Some(15);
+        assert_eq!(
+            strict_solve(wrong_final),
+            Err(Error::PreCheck(PreCheck::NumberDomain(
+                game::Error::WrongFinalNumber {
+                    actual: 15,
+                    expected: 16,
+                }
+            )))
+        );
+
+        assert_eq!(
+            strict_solve(Game::example().into_board()),
+            Solver::solve(Game::example().into_board())
+        );
+    }
 }
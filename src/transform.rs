@@ -0,0 +1,204 @@
+#![allow(dead_code)]
+
+use crate::game::*;
+use array2d::Array2D;
+
+// These work on any board, but numbers move along with their cells and the arrows turn to match,
+// so a solved board is still solved afterwards.
+
+// Rotate a quarter turn clockwise
+pub fn rotate_solution_90(solved: &Board) -> Board {
+    let num_rows = solved.num_rows();
+    let rows = (0..solved.num_columns())
+        .map(|column| {
+            (0..num_rows)
+                .rev()
+                .map(|row| map_pointer(solved[(row, column)], rotate_direction))
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    Array2D::from_rows(&rows).expect("Rows all have the same length")
+}
+
+// Mirror left to right
+pub fn flip_solution_horizontal(solved: &Board) -> Board {
+    let rows = solved
+        .rows_iter()
+        .map(|row| {
+            row.rev()
+                .map(|&cell| map_pointer(cell, flip_direction_horizontal))
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    Array2D::from_rows(&rows).expect("Rows all have the same length")
+}
+
+// Mirror top to bottom
+pub fn flip_solution_vertical(solved: &Board) -> Board {
+    let mut rows = solved
+        .rows_iter()
+        .map(|row| {
+            row.map(|&cell| map_pointer(cell, flip_direction_vertical))
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    rows.reverse();
+    Array2D::from_rows(&rows).expect("Rows all have the same length")
+}
+
+// Run the chain backward, so number `k` becomes `N + 1 - k`. Each cell then points back at the cell
+// that used to come before it, which is the opposite of that cell's arrow, and the old first cell
+// becomes the final cell. Gives `None` if the board isn't a solved game.
+pub fn reverse_chain(solved: &Board) -> Option<Board> {
+    let path = Game::new(solved.clone()).ok()?.solution_path()?;
+    let num_elements = path.len();
+    let mut reversed = solved.clone();
+    for (i, &row_column) in path.iter().enumerate() {
+        let pointer = match i.checked_sub(1) {
+            Some(previous) => match solved[path[previous]].pointer {
+                Pointer::Go(direction) => Pointer::Go(direction.opposite()),
+                _ => unreachable!("Only the last cell on the path is final"),
+            },
+            None => Pointer::Final,
+        };
+        reversed[row_column] = Cell {
+            pointer,
+            number: Some(num_elements - i),
+        };
+    }
+    Some(reversed)
+}
+
+fn map_pointer(cell: Cell, f: fn(Direction) -> Direction) -> Cell {
+    let pointer = match cell.pointer {
+        Pointer::Go(direction) => Pointer::Go(f(direction)),
+        pointer => pointer,
+    };
+    Cell { pointer, ..cell }
+}
+
+fn rotate_direction(direction: Direction) -> Direction {
+    match direction {
+        Direction::North => Direction::East,
+        Direction::Northeast => Direction::Southeast,
+        Direction::East => Direction::South,
+        Direction::Southeast => Direction::Southwest,
+        Direction::South => Direction::West,
+        Direction::Southwest => Direction::Northwest,
+        Direction::West => Direction::North,
+        Direction::Northwest => Direction::Northeast,
+    }
+}
+
+fn flip_direction_horizontal(direction: Direction) -> Direction {
+    match direction {
+        Direction::Northeast => Direction::Northwest,
+        Direction::East => Direction::West,
+        Direction::Southeast => Direction::Southwest,
+        Direction::Southwest => Direction::Southeast,
+        Direction::West => Direction::East,
+        Direction::Northwest => Direction::Northeast,
+        Direction::North | Direction::South => direction,
+    }
+}
+
+fn flip_direction_vertical(direction: Direction) -> Direction {
+    match direction {
+        Direction::North => Direction::South,
+        Direction::Northeast => Direction::Southeast,
+        Direction::Southeast => Direction::Northeast,
+        Direction::South => Direction::North,
+        Direction::Southwest => Direction::Northwest,
+        Direction::Northwest => Direction::Southwest,
+        Direction::East | Direction::West => direction,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generate::Generator;
+    use crate::solver::Solver;
+
+    fn solved_example() -> Board {
+        Solver::solve(Game::example().into_board()).unwrap()
+    }
+
+    #[test]
+    fn test_rotate_solution() {
+        let solved = solved_example();
+        let mut rotated = solved.clone();
+        for _ in 0..4 {
+            rotated = rotate_solution_90(&rotated);
+            assert!(Game::new(rotated.clone()).unwrap().is_solved());
+        }
+        assert_eq!(rotated, solved);
+
+        let rotated = rotate_solution_90(&solved);
+        assert_eq!(rotated[(0, 3)].number, Some(1));
+        assert_eq!(rotated[(0, 3)].pointer, Pointer::Go(Direction::South));
+    }
+
+    #[test]
+    fn test_rotate_rectangle() {
+        let board =
+            Generator::generate_path(&[(0, 0), (0, 1), (0, 2), (1, 2), (1, 1), (1, 0)], 2, 3)
+                .unwrap();
+        let rotated = rotate_solution_90(&board);
+        assert_eq!((rotated.num_rows(), rotated.num_columns()), (3, 2));
+        assert!(Game::new(rotated).unwrap().is_solved());
+    }
+
+    #[test]
+    fn test_flip_solution() {
+        let solved = solved_example();
+        for flipped in &[
+            flip_solution_horizontal(&solved),
+            flip_solution_vertical(&solved),
+        ] {
+            assert!(Game::new(flipped.clone()).unwrap().is_solved());
+            assert_ne!(flipped, &solved);
+        }
+        assert_eq!(
+            flip_solution_horizontal(&flip_solution_horizontal(&solved)),
+            solved
+        );
+        assert_eq!(
+            flip_solution_vertical(&flip_solution_vertical(&solved)),
+            solved
+        );
+    }
+
+    #[test]
+    fn test_reverse_chain() {
+        let solved = solved_example();
+        let reversed = reverse_chain(&solved).unwrap();
+        assert!(Game::new(reversed.clone()).unwrap().is_solved());
+        assert_eq!(
+            reversed[(0, 0)],
+            Cell::new(Pointer::Final, Some(16)).unwrap()
+        );
+        assert_eq!(
+            reversed[(3, 3)],
+            Cell::new(Pointer::Go(Direction::West), Some(1)).unwrap()
+        );
+        assert_eq!(reverse_chain(&reversed), Some(solved));
+
+        let go = |direction, number| Cell::new(Pointer::Go(direction), Some(number)).unwrap();
+        let blocked = Array2D::from_rows(&vec![
+            vec![go(Direction::East, 1), go(Direction::Southwest, 2)],
+            vec![Cell::new(Pointer::Final, Some(3)).unwrap(), Cell::blocked()],
+        ])
+        .unwrap();
+        let reversed = reverse_chain(&blocked).unwrap();
+        assert!(Game::new(reversed.clone()).unwrap().is_solved());
+        assert_eq!(reversed[(1, 1)], Cell::blocked());
+        assert_eq!(reverse_chain(&reversed), Some(blocked));
+
+        // Clue boards and partly solved boards have no chain to reverse
+        assert_eq!(reverse_chain(Game::example().board()), None);
+        let mut partial = solved_example();
+        partial[(1, 1)].number = None;
+        assert_eq!(reverse_chain(&partial), None);
+    }
+}
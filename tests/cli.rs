@@ -0,0 +1,88 @@
+mod common;
+
+use common::{run, run_solve, write_puzzle};
+use std::env;
+use std::fs;
+
+#[test]
+fn solve_good_file() {
+    let path = write_puzzle("good", "e1,e,s,w3\ns,s12,w5,w\nse,w,e,n\ne,e,n,*16\n");
+    let (success, stdout, stderr) = run_solve(&path);
+    fs::remove_file(&path).unwrap();
+    assert!(success, "{}", stderr);
+    assert_eq!(stdout.lines().count(), 4);
+    assert!(stdout.contains("16 ☆"));
+}
+
+#[test]
+fn solve_bad_file() {
+    let path = write_puzzle("bad", "e1,e,s,w3\ns,x,w5,w\nse,w,e,n\ne,e,n,*16\n");
+    let (success, stdout, stderr) = run_solve(&path);
+    fs::remove_file(&path).unwrap();
+    assert!(!success);
+    assert_eq!(stdout, "");
+    assert_eq!(
+        stderr,
+        format!(
+            "error: could not parse {}\n  caused by: at line 2, column 3: unrecognized cell\n",
+            path.display()
+        )
+    );
+}
+
+#[test]
+fn solve_missing_file() {
+    let path = env::temp_dir().join("arrows-cli-does-not-exist.txt");
+    let (success, _, stderr) = run_solve(&path);
+    assert!(!success);
+    assert!(stderr.starts_with(&format!(
+        "error: could not read {}\n  caused by: ",
+        path.display()
+    )));
+}
+
+// The numbers on a board in row-major order, from either `board_to_text` or `Game::to_strings`
+fn numbers(text: &str) -> Vec<usize> {
+    text.split(|c: char| !c.is_ascii_digit())
+        .filter(|number| !number.is_empty())
+        .map(|number| number.parse().unwrap())
+        .collect()
+}
+
+#[test]
+fn generate_then_solve() {
+    let (success, stdout, stderr) = run(&[
+        "generate",
+        "--rows",
+        "4",
+        "--cols",
+        "5",
+        "--difficulty",
+        "hard",
+        "--seed",
+        "42",
+        "--solution",
+    ]);
+    assert!(success, "{}", stderr);
+    let mut boards = stdout.trim_end().split("\n\n");
+    let clues = boards.next().unwrap();
+    let solution = boards.next().unwrap();
+    assert_eq!(boards.next(), None);
+    assert_eq!(clues.lines().count(), 4);
+    assert!(clues.lines().all(|row| row.split(',').count() == 5));
+
+    let path = write_puzzle("generated", clues);
+    let (success, stdout, stderr) = run_solve(&path);
+    fs::remove_file(&path).unwrap();
+    assert!(success, "{}", stderr);
+    assert_eq!(numbers(&stdout), numbers(solution));
+    assert_eq!(numbers(solution).len(), 20);
+}
+
+#[test]
+fn generate_too_large() {
+    let (success, stdout, stderr) = run(&["generate", "--rows", "1000"]);
+    assert!(!success);
+    assert_eq!(stdout, "");
+    assert!(stderr.starts_with("error: --rows must be between 1 and 20: 1000\nUsage: "));
+}
@@ -0,0 +1,28 @@
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub fn write_puzzle(name: &str, contents: &str) -> PathBuf {
+    let file_name = format!("arrows-test-{}-{}.txt", std::process::id(), name);
+    let path = env::temp_dir().join(file_name);
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+pub fn run_solve(path: &Path) -> (bool, String, String) {
+    run(&["solve".as_ref(), path.as_os_str()])
+}
+
+pub fn run<S: AsRef<OsStr>>(args: &[S]) -> (bool, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_arrows"))
+        .args(args)
+        .output()
+        .unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
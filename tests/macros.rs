@@ -0,0 +1,20 @@
+use arrows::game::{Error, Game};
+
+#[test]
+fn board_macro() {
+    let board = arrows::board![
+        [e 1, e, s, w 3],
+        [s, s 12, w 5, w],
+        [se, w, e, n],
+        [e, e, n, *16],
+    ];
+    assert_eq!(&board, Game::example().board());
+    assert_eq!(
+        arrows::try_board![[e 1, s], [*3]],
+        Err(Error::RaggedRows {
+            row: 1,
+            expected: 2,
+            found: 1,
+        })
+    );
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::{run_solve, write_puzzle};
+use std::fs;
+
+// Solve the clue rows with the CLI and compare against the known unique solution, as printed by
+// `Game::to_strings`
+fn assert_solves(name: &str, clues: &[&str], expected: &[&str]) {
+    let path = write_puzzle(name, &clues.join("\n"));
+    let (success, stdout, stderr) = run_solve(&path);
+    fs::remove_file(&path).unwrap();
+    assert!(success, "{}", stderr);
+    assert_eq!(stdout.lines().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn example() {
+    assert_solves(
+        "example",
+        &["e1,e,s,w3", "s,s12,w5,w", "se,w,e,n", "e,e,n,*16"],
+        &[
+            " 1 ⇒| 2 ⇒| 4 ⇓| 3 ⇐",
+            " 6 ⇓|12 ⇓| 5 ⇐|11 ⇐",
+            "14 ⇘|13 ⇐| 9 ⇒|10 ⇑",
+            " 7 ⇒|15 ⇒| 8 ⇑|16 ☆",
+        ],
+    );
+}
+
+#[test]
+fn demo_4x4() {
+    assert_solves(
+        "demo_4x4",
+        &["e1,s,w5,sw", "se,se,s,sw", "ne,e,w,n", "n,w,n,*16"],
+        &[
+            " 1 ⇒| 6 ⇓| 5 ⇐| 2 ⇙",
+            " 9 ⇘|15 ⇘| 3 ⇓|12 ⇙",
+            "14 ⇗|10 ⇒|13 ⇐|11 ⇑",
+            " 8 ⇑| 7 ⇐| 4 ⇑|16 ☆",
+        ],
+    );
+}
+
+#[test]
+fn small_3x3() {
+    assert_solves(
+        "small_3x3",
+        &["e1,e,s", "se,w5,w4", "e,w,*9"],
+        &["1 ⇒|2 ⇒|3 ⇓", "6 ⇘|5 ⇐|4 ⇐", "8 ⇒|7 ⇐|9 ☆"],
+    );
+}
+
+#[test]
+fn demo_8x8_first() {
+    assert_solves(
+        "demo_8x8_first",
+        &[
+            "s1,se,s21,se,s,e,sw,sw39",
+            "e,e55,nw,s,sw56,w42,w41,sw8",
+            "e,se,e,s,n,nw,n,s",
+            "e,e48,e,w,s,se28,w,s",
+            "s,s,se,se60,sw,n,se,w",
+            "n6,n58,n,nw,e,n,w12,sw",
+            "n,ne,e,ne,n,nw,s,nw24",
+            "e,n,n19,n,w33,n37,w,*64",
+        ],
+        &[
+            " 1 ⇓|26 ⇘|21 ⇓|15 ⇘|52 ⇓|38 ⇒| 4 ⇙|39 ⇙",
+            " 7 ⇒|55 ⇒|25 ⇖|43 ⇓|56 ⇙|42 ⇐|41 ⇐| 8 ⇙",
+            " 2 ⇒|59 ⇘|22 ⇒|44 ⇓|51 ⇑|14 ⇖| 3 ⇑|23 ⇓",
+            "46 ⇒|48 ⇒|62 ⇒|45 ⇐|49 ⇓|28 ⇘|47 ⇐|63 ⇓",
+            "17 ⇓|57 ⇓|36 ⇘|60 ⇘|53 ⇙|27 ⇑|29 ⇘|16 ⇐",
+            " 6 ⇑|58 ⇑|20 ⇑|35 ⇖|11 ⇒|13 ⇑|12 ⇐|30 ⇙",
+            " 5 ⇑|40 ⇗| 9 ⇒|10 ⇗|50 ⇑|61 ⇖|31 ⇓|24 ⇖",
+            "18 ⇒|54 ⇑|19 ⇑|34 ⇑|33 ⇐|37 ⇑|32 ⇐|64 ☆",
+        ],
+    );
+}
+
+#[test]
+fn demo_8x8_second() {
+    assert_solves(
+        "demo_8x8_second",
+        &[
+            "s1,se,se,e46,w48,s14,s,w",
+            "ne5,se59,s,w,se39,sw,sw,s",
+            "se,se,se61,se,sw,s55,w,w25",
+            "s,n,w57,sw,nw7,n,s52,w",
+            "se,s22,se,w21,s,n13,sw,s",
+            "n,ne,nw,n,w,n,s27,n",
+            "e,e,e,n,n,nw56,nw,w41",
+            "e,ne,ne12,n34,e,nw37,n,*64",
+        ],
+        &[
+            " 1 ⇓| 6 ⇘|49 ⇘|46 ⇒|48 ⇐|14 ⇓|19 ⇓|47 ⇐",
+            " 5 ⇗|59 ⇘| 8 ⇓| 4 ⇐|39 ⇘|16 ⇙|20 ⇙|24 ⇓",
+            " 2 ⇘|30 ⇘|61 ⇘|26 ⇘|17 ⇙|55 ⇓|29 ⇐|25 ⇐",
+            "10 ⇓|58 ⇑|57 ⇐|35 ⇙| 7 ⇖|15 ⇑|52 ⇓|51 ⇐",
+            "33 ⇘|22 ⇓|36 ⇘|21 ⇐|62 ⇓|13 ⇑|53 ⇙|40 ⇓",
+            "32 ⇑|18 ⇗| 9 ⇖| 3 ⇑|31 ⇐|54 ⇑|27 ⇓|50 ⇑",
+            "42 ⇒|43 ⇒|44 ⇒|45 ⇑|38 ⇑|56 ⇖|60 ⇖|41 ⇐",
+            "11 ⇒|23 ⇗|12 ⇗|34 ⇑|63 ⇒|37 ⇖|28 ⇑|64 ☆",
+        ],
+    );
+}